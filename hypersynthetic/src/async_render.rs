@@ -0,0 +1,211 @@
+//! Rendering of async components for the [html_async](crate::html_async) macro.
+//!
+//! The macro builds the tree right away and puts a hidden placeholder node wherever an async
+//! component is called. The returned [RenderFuture] polls all of these components concurrently
+//! and replaces the placeholders with what they render. None of this needs to be used directly.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::task::{Context, Poll};
+
+use crate::{ElementData, HtmlFragment, Node, context};
+
+type BoxFuture<'a> = Pin<Box<dyn Future<Output = HtmlFragment> + Send + 'a>>;
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// What a component returns: either a rendered [HtmlFragment] or a future of one.
+/// `Marker` is either [Rendered] or [Rendering].
+pub trait IntoRender<'a, Marker> {
+    fn into_render(self) -> Result<HtmlFragment, BoxFuture<'a>>;
+}
+
+pub struct Rendered;
+
+pub struct Rendering;
+
+impl IntoRender<'_, Rendered> for HtmlFragment {
+    fn into_render(self) -> Result<HtmlFragment, BoxFuture<'static>> {
+        Ok(self)
+    }
+}
+
+impl<'a, F> IntoRender<'a, Rendering> for F
+where
+    F: Future<Output = HtmlFragment> + Send + 'a,
+{
+    fn into_render(self) -> Result<HtmlFragment, BoxFuture<'a>> {
+        Err(Box::pin(self))
+    }
+}
+
+/// The async components called while building a tree.
+#[derive(Default)]
+pub struct Pending<'a> {
+    ids: Vec<usize>,
+    futures: Vec<BoxFuture<'a>>,
+}
+
+impl<'a> Pending<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the nodes of a rendered component, or a placeholder for a future one.
+    /// The future will see the contexts provided at this moment.
+    pub fn defer<Marker>(&mut self, output: impl IntoRender<'a, Marker>) -> Vec<Node> {
+        let future = match output.into_render() {
+            Ok(fragment) => return fragment.get_nodes(),
+            Err(future) => future,
+        };
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let contexts = context::snapshot();
+        self.ids.push(id);
+        self.futures
+            .push(Box::pin(WithContexts { contexts, future }));
+        vec![Node::Pending(id)]
+    }
+
+    /// Waits for every component and puts what they render into `fragment`.
+    pub fn resolve(self, fragment: HtmlFragment) -> RenderFuture<'a> {
+        let Pending { ids, futures } = self;
+        RenderFuture(Box::pin(async move {
+            let fragments = JoinAll::new(futures).await;
+            let mut rendered: HashMap<usize, HtmlFragment> =
+                ids.into_iter().zip(fragments).collect();
+            let mut uses = HashMap::new();
+            count_pending(&fragment, &mut uses);
+            for fragment in rendered.values() {
+                count_pending(fragment, &mut uses);
+            }
+            HtmlFragment::new(replace_pending(fragment, &mut rendered, &mut uses))
+        }))
+    }
+}
+
+/// The result of [html_async](crate::html_async). It can be awaited anywhere, for example
+/// in an axum or rocket handler that returns [HtmlFragment].
+pub struct RenderFuture<'a>(BoxFuture<'a>);
+
+impl Future for RenderFuture<'_> {
+    type Output = HtmlFragment;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<HtmlFragment> {
+        self.0.as_mut().poll(cx)
+    }
+}
+
+/// Counts how often every pending node appears. It appears more than once when a component
+/// copies its children.
+fn count_pending(fragment: &HtmlFragment, uses: &mut HashMap<usize, usize>) {
+    for node in fragment {
+        match node {
+            Node::Pending(id) => *uses.entry(*id).or_default() += 1,
+            Node::Element(element) => count_pending(&element.children, uses),
+            _ => {}
+        }
+    }
+}
+
+/// Replaces pending nodes, including the ones inside rendered components, which get them
+/// through their children. A rendered component is moved into its last use and copied into
+/// the others.
+fn replace_pending(
+    HtmlFragment::Nodes(fragment): HtmlFragment,
+    rendered: &mut HashMap<usize, HtmlFragment>,
+    uses: &mut HashMap<usize, usize>,
+) -> Vec<Node> {
+    let mut nodes = Vec::with_capacity(fragment.len());
+    for node in fragment {
+        match node {
+            Node::Pending(id) => {
+                let uses_left = uses.get_mut(&id).map_or(0, |uses| {
+                    *uses -= 1;
+                    *uses
+                });
+                let fragment = if uses_left == 0 {
+                    rendered.remove(&id)
+                } else {
+                    // The pending nodes in the copy are used once more as well
+                    let copy = rendered.get(&id).cloned();
+                    if let Some(copy) = &copy {
+                        count_pending(copy, uses);
+                    }
+                    copy
+                };
+                match fragment {
+                    Some(fragment) => nodes.extend(replace_pending(fragment, rendered, uses)),
+                    None => nodes.push(Node::Pending(id)),
+                }
+            }
+            Node::Element(element) => {
+                let children = replace_pending(element.children, rendered, uses);
+                nodes.push(Node::Element(ElementData {
+                    children: HtmlFragment::new(children),
+                    ..element
+                }));
+            }
+            node => nodes.push(node),
+        }
+    }
+    nodes
+}
+
+/// Polls a component with the contexts it was called with.
+struct WithContexts<'a> {
+    contexts: Vec<context::SharedContext>,
+    future: BoxFuture<'a>,
+}
+
+impl Future for WithContexts<'_> {
+    type Output = HtmlFragment;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<HtmlFragment> {
+        let this = &mut *self;
+        let _guard = context::enter(&this.contexts);
+        this.future.as_mut().poll(cx)
+    }
+}
+
+/// Polls all futures concurrently and returns their outputs in order.
+struct JoinAll<'a> {
+    futures: Vec<Option<BoxFuture<'a>>>,
+    outputs: Vec<Option<HtmlFragment>>,
+}
+
+impl<'a> JoinAll<'a> {
+    fn new(futures: Vec<BoxFuture<'a>>) -> Self {
+        let outputs = futures.iter().map(|_| None).collect();
+        JoinAll {
+            futures: futures.into_iter().map(Some).collect(),
+            outputs,
+        }
+    }
+}
+
+impl Future for JoinAll<'_> {
+    type Output = Vec<HtmlFragment>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Vec<HtmlFragment>> {
+        let this = &mut *self;
+        for (slot, output) in this.futures.iter_mut().zip(this.outputs.iter_mut()) {
+            if let Some(future) = slot
+                && let Poll::Ready(fragment) = future.as_mut().poll(cx)
+            {
+                *output = Some(fragment);
+                *slot = None;
+            }
+        }
+        if this.futures.iter().any(Option::is_some) {
+            return Poll::Pending;
+        }
+        Poll::Ready(
+            this.outputs
+                .iter_mut()
+                .map(|output| output.take().unwrap())
+                .collect(),
+        )
+    }
+}
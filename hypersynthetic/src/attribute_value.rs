@@ -0,0 +1,296 @@
+//! Conversion of `{expression}` and `{..spread}` attribute values in the [html](crate::html) macro.
+//!
+//! An expression can be anything that implements [Display], or an [Option] of it,
+//! in which case `None` omits the attribute, or a [Trusted] value, which is not escaped for
+//! the [AttributeContext]. The macro picks the right conversion with autoref-based dispatch,
+//! so none of this needs to be used directly.
+
+use std::fmt::Display;
+
+use crate::escape::{AttributeContext, Trusted, unescape_attribute};
+use crate::{Attribute, Attributes, escape_attribute};
+
+pub struct AttributeValue<'a, T: ?Sized>(pub &'a T);
+
+pub trait OptionalAttributeValue {
+    fn to_attribute_value(&self, context: AttributeContext) -> Option<String>;
+}
+
+impl<T: Display> OptionalAttributeValue for AttributeValue<'_, Option<T>> {
+    fn to_attribute_value(&self, context: AttributeContext) -> Option<String> {
+        self.0
+            .as_ref()
+            .map(|value| context.escape_value(&value.to_string()))
+    }
+}
+
+pub trait TrustedAttributeValue {
+    fn to_attribute_value(&self, context: AttributeContext) -> Option<String>;
+}
+
+impl<T: Display> TrustedAttributeValue for AttributeValue<'_, Trusted<T>> {
+    fn to_attribute_value(&self, _context: AttributeContext) -> Option<String> {
+        Some(self.0.0.to_string())
+    }
+}
+
+impl<T: Display> TrustedAttributeValue for AttributeValue<'_, Option<Trusted<T>>> {
+    fn to_attribute_value(&self, _context: AttributeContext) -> Option<String> {
+        self.0.as_ref().map(|value| value.0.to_string())
+    }
+}
+
+/// A value interpolated into an attribute, like `title="Hello, {name}"`.
+pub trait TrustedSegmentValue {
+    fn to_segment_value(&self, context: AttributeContext) -> String;
+}
+
+impl<T: Display> TrustedSegmentValue for AttributeValue<'_, Trusted<T>> {
+    fn to_segment_value(&self, _context: AttributeContext) -> String {
+        self.0.0.to_string()
+    }
+}
+
+pub trait DisplayAttributeValue {
+    fn to_attribute_value(&self, context: AttributeContext) -> Option<String>;
+    fn to_segment_value(&self, context: AttributeContext) -> String;
+}
+
+impl<T: Display + ?Sized> DisplayAttributeValue for &AttributeValue<'_, T> {
+    fn to_attribute_value(&self, context: AttributeContext) -> Option<String> {
+        Some(context.escape_value(&self.0.to_string()))
+    }
+
+    fn to_segment_value(&self, context: AttributeContext) -> String {
+        context.escape_segment(&self.0.to_string())
+    }
+}
+
+/// The value of `hidden`, which is a toggle for `bool` and `Option<bool>`, and rendered as
+/// a value like `until-found` otherwise. `None` omits the attribute, `Some(None)` renders
+/// it without a value.
+pub trait ToggleHiddenValue {
+    fn to_hidden_value(&self) -> Option<Option<String>>;
+}
+
+impl ToggleHiddenValue for AttributeValue<'_, bool> {
+    fn to_hidden_value(&self) -> Option<Option<String>> {
+        self.0.then_some(None)
+    }
+}
+
+impl ToggleHiddenValue for AttributeValue<'_, Option<bool>> {
+    fn to_hidden_value(&self) -> Option<Option<String>> {
+        (*self.0 == Some(true)).then_some(None)
+    }
+}
+
+pub trait OptionalHiddenValue {
+    fn to_hidden_value(&self) -> Option<Option<String>>;
+}
+
+impl<T: Display> OptionalHiddenValue for &AttributeValue<'_, Option<T>> {
+    fn to_hidden_value(&self) -> Option<Option<String>> {
+        self.0.as_ref().map(|value| Some(value.to_string()))
+    }
+}
+
+pub trait DisplayHiddenValue {
+    fn to_hidden_value(&self) -> Option<Option<String>>;
+}
+
+impl<T: Display + ?Sized> DisplayHiddenValue for &AttributeValue<'_, T> {
+    fn to_hidden_value(&self) -> Option<Option<String>> {
+        Some(Some(self.0.to_string()))
+    }
+}
+
+/// Builds the value of a `class` attribute out of `class` and `class:name` attributes.
+/// Every class is included once, in the order it was first added.
+#[derive(Default)]
+pub struct ClassList {
+    classes: Vec<String>,
+}
+
+impl ClassList {
+    /// Adds whitespace-separated classes.
+    pub fn add(&mut self, classes: &str) {
+        for class in classes.split_whitespace() {
+            if !self.classes.iter().any(|existing| existing == class) {
+                self.classes.push(class.to_owned());
+            }
+        }
+    }
+
+    pub fn to_attribute_value(&self) -> Option<String> {
+        if self.classes.is_empty() {
+            None
+        } else {
+            Some(self.classes.join(" "))
+        }
+    }
+}
+
+/// Builds the value of a `style` attribute out of `style` and `style:property` attributes.
+/// A property that is set several times keeps its first position and its last value.
+#[derive(Default)]
+pub struct StyleList {
+    declarations: Vec<(String, String)>,
+}
+
+impl StyleList {
+    /// Adds declarations like `color: red; margin: 0`.
+    pub fn add_declarations(&mut self, style: &str) {
+        for declaration in split_declarations(style) {
+            if let Some((property, value)) = declaration.split_once(':') {
+                self.set(property, value);
+            }
+        }
+    }
+
+    pub fn set(&mut self, property: &str, value: &str) {
+        let (property, value) = (property.trim(), value.trim());
+        if property.is_empty() || value.is_empty() {
+            return;
+        }
+        match self
+            .declarations
+            .iter_mut()
+            .find(|(existing, _)| existing == property)
+        {
+            Some((_, existing_value)) => *existing_value = value.to_owned(),
+            None => self
+                .declarations
+                .push((property.to_owned(), value.to_owned())),
+        }
+    }
+
+    pub fn to_attribute_value(&self) -> Option<String> {
+        if self.declarations.is_empty() {
+            return None;
+        }
+        let declarations: Vec<String> = self
+            .declarations
+            .iter()
+            .map(|(property, value)| format!("{property}: {value};"))
+            .collect();
+        Some(declarations.join(" "))
+    }
+}
+
+/// Splits on `;` that are not inside quotes or parentheses, like in `url("a;b")`.
+pub(crate) fn split_declarations(style: &str) -> Vec<&str> {
+    let mut declarations = Vec::new();
+    let mut depth = 0;
+    let mut quote = None;
+    let mut start = 0;
+    for (i, ch) in style.char_indices() {
+        match (quote, ch) {
+            (Some(q), _) if ch == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(ch),
+            (None, '(') => depth += 1,
+            (None, ')') => depth -= 1,
+            (None, ';') if depth == 0 => {
+                declarations.push(&style[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    declarations.push(&style[start..]);
+    declarations
+}
+
+/// The expression of a `{..spread}` attribute. It is either [Attributes] (owned or borrowed),
+/// or anything that iterates over `(name, value)` pairs where both implement [Display].
+///
+/// Values are escaped for the [AttributeContext] of their name, unless they were set with
+/// [Attributes::set_trusted]. A spread attribute replaces an earlier attribute with the same
+/// name, except for `class` and `style`, which are merged with it.
+pub struct Spread<T>(pub T);
+
+pub trait SpreadAttributes {
+    fn spread_into(self, attributes: &mut Vec<Attribute>);
+}
+
+impl SpreadAttributes for Spread<Attributes> {
+    fn spread_into(self, attributes: &mut Vec<Attribute>) {
+        spread_attributes(attributes, &self.0);
+    }
+}
+
+impl SpreadAttributes for Spread<&Attributes> {
+    fn spread_into(self, attributes: &mut Vec<Attribute>) {
+        spread_attributes(attributes, self.0);
+    }
+}
+
+impl<I, K, V> SpreadAttributes for Spread<I>
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: Display,
+    V: Display,
+{
+    fn spread_into(self, attributes: &mut Vec<Attribute>) {
+        for (name, value) in self.0 {
+            push_spread_attribute(
+                attributes,
+                &name.to_string(),
+                Some(&value.to_string()),
+                false,
+            );
+        }
+    }
+}
+
+fn spread_attributes(attributes: &mut Vec<Attribute>, spread: &Attributes) {
+    for attr in spread {
+        let trusted = spread.is_trusted(&attr.name);
+        push_spread_attribute(attributes, &attr.name, attr.value.as_deref(), trusted);
+    }
+}
+
+fn push_spread_attribute(
+    attributes: &mut Vec<Attribute>,
+    name: &str,
+    value: Option<&str>,
+    trusted: bool,
+) {
+    let value = match value {
+        Some(value) if !trusted => Some(AttributeContext::of_attribute(name).escape_value(value)),
+        value => value.map(str::to_owned),
+    };
+    let name = escape_attribute(name).into_owned();
+    let value = value.map(|value| escape_attribute(value).into_owned());
+    merge_attribute(attributes, name, value);
+}
+
+/// Adds an attribute whose name and value are escaped for HTML. An earlier `class` or `style`
+/// attribute is merged with it like by [ClassList] and [StyleList]; any other attribute with
+/// the same name is replaced.
+pub fn merge_attribute(attributes: &mut Vec<Attribute>, name: String, value: Option<String>) {
+    let Some(attr) = attributes.iter_mut().find(|attr| attr.name == name) else {
+        attributes.push(Attribute { name, value });
+        return;
+    };
+    let merged = match (name.as_str(), &attr.value, &value) {
+        ("class", Some(existing), Some(added)) => {
+            let mut class_list = ClassList::default();
+            class_list.add(&unescape_attribute(existing));
+            class_list.add(&unescape_attribute(added));
+            class_list.to_attribute_value()
+        }
+        ("style", Some(existing), Some(added)) => {
+            let mut style_list = StyleList::default();
+            style_list.add_declarations(&unescape_attribute(existing));
+            style_list.add_declarations(&unescape_attribute(added));
+            style_list.to_attribute_value()
+        }
+        _ => {
+            attr.value = value;
+            return;
+        }
+    };
+    attr.value = merged.map(|value| escape_attribute(value).into_owned());
+}
@@ -1,4 +1,7 @@
-use crate::HtmlFragment;
+use std::fmt::Display;
+
+use crate::escape::Trusted;
+use crate::{Attributes, HtmlFragment};
 
 pub trait Component<P> {
     fn call(&self, props: P) -> HtmlFragment;
@@ -28,6 +31,39 @@ where
     }
 }
 
+/// Calls a component when no content is passed for its default slot.
+/// Components with a default slot get an empty [HtmlFragment].
+///
+/// `Marker` is either [WithoutSlot] or [WithSlot]; it only exists so that both kinds
+/// of component functions can implement this trait.
+pub trait ComponentWithOptionalSlot<P, Marker> {
+    fn call_with_optional_slot(&self, props: P) -> HtmlFragment;
+}
+
+pub struct WithoutSlot;
+
+pub struct WithSlot;
+
+impl<P, F> ComponentWithOptionalSlot<P, WithoutSlot> for F
+where
+    F: Fn(P) -> HtmlFragment,
+    P: Props,
+{
+    fn call_with_optional_slot(&self, props: P) -> HtmlFragment {
+        self(props)
+    }
+}
+
+impl<P, F> ComponentWithOptionalSlot<P, WithSlot> for F
+where
+    F: Fn(HtmlFragment, P) -> HtmlFragment,
+    P: Props,
+{
+    fn call_with_optional_slot(&self, props: P) -> HtmlFragment {
+        self(HtmlFragment::default(), props)
+    }
+}
+
 pub trait Props {
     type Builder;
 
@@ -60,6 +96,43 @@ pub fn component_with_slots_props_builder<P: PropsOrNoPropsBuilder>(
     <P as PropsOrNoPropsBuilder>::builder_or_not()
 }
 
+pub fn component_with_optional_slot_props_builder<P: PropsOrNoPropsBuilder, Marker>(
+    _f: &impl ComponentWithOptionalSlot<P, Marker>,
+) -> <P as PropsOrNoPropsBuilder>::Builder {
+    <P as PropsOrNoPropsBuilder>::builder_or_not()
+}
+
+/// Implemented for the props builders of components declared with `#[component(rest_attrs)]`.
+/// Attributes that are not props are only accepted by these components.
+#[diagnostic::on_unimplemented(
+    message = "this component doesn't have a prop for every attribute",
+    note = "use `#[component(rest_attrs)]` with an `Attributes` parameter to accept other attributes"
+)]
+pub trait AcceptsRestAttributes: Sized {
+    fn rest_attribute(self, name: &str, value: impl RestAttributeValue) -> Self;
+
+    fn rest_flag(self, name: &str) -> Self;
+}
+
+/// The value of an attribute that is not a prop. It is escaped for its
+/// [AttributeContext](crate::escape::AttributeContext) when the rest attributes are spread,
+/// unless it is [Trusted].
+pub trait RestAttributeValue {
+    fn set_rest_attribute(self, attrs: &mut Attributes, name: &str);
+}
+
+impl<T: Display> RestAttributeValue for T {
+    fn set_rest_attribute(self, attrs: &mut Attributes, name: &str) {
+        attrs.set(name, self);
+    }
+}
+
+impl<T: Display> RestAttributeValue for Trusted<T> {
+    fn set_rest_attribute(self, attrs: &mut Attributes, name: &str) {
+        attrs.set_trusted(name, self.0);
+    }
+}
+
 pub fn component_view<P: Props>(component: &impl Component<P>, props: P) -> HtmlFragment {
     component.call(props)
 }
@@ -71,3 +144,114 @@ pub fn component_with_slots_view<P: Props>(
 ) -> HtmlFragment {
     component.call_with_slots(children, props)
 }
+
+pub fn component_with_optional_slot_view<P: Props, Marker>(
+    component: &impl ComponentWithOptionalSlot<P, Marker>,
+    props: P,
+) -> HtmlFragment {
+    component.call_with_optional_slot(props)
+}
+
+/// Calls a component whose function may return anything, like a future for async components.
+/// Used by [html_async](crate::html_async); `Marker` works as in [ComponentWithOptionalSlot].
+pub trait Render<P, Marker> {
+    type Output;
+
+    fn render(&self, props: P) -> Self::Output;
+}
+
+impl<P, R, F> Render<P, WithoutSlot> for F
+where
+    F: Fn(P) -> R,
+    P: Props,
+{
+    type Output = R;
+
+    fn render(&self, props: P) -> R {
+        self(props)
+    }
+}
+
+impl<P, R, F> Render<P, WithSlot> for F
+where
+    F: Fn(HtmlFragment, P) -> R,
+    P: Props,
+{
+    type Output = R;
+
+    fn render(&self, props: P) -> R {
+        self(HtmlFragment::default(), props)
+    }
+}
+
+/// Like [Render], for components called with content for their default slot.
+pub trait RenderWithSlots<P> {
+    type Output;
+
+    fn render_with_slots(&self, children: HtmlFragment, props: P) -> Self::Output;
+}
+
+impl<P, R, F> RenderWithSlots<P> for F
+where
+    F: Fn(HtmlFragment, P) -> R,
+    P: Props,
+{
+    type Output = R;
+
+    fn render_with_slots(&self, children: HtmlFragment, props: P) -> R {
+        self(children, props)
+    }
+}
+
+pub fn render_props_builder<P: PropsOrNoPropsBuilder, Marker>(
+    _f: &impl Render<P, Marker>,
+) -> <P as PropsOrNoPropsBuilder>::Builder {
+    <P as PropsOrNoPropsBuilder>::builder_or_not()
+}
+
+pub fn render_with_slots_props_builder<P: PropsOrNoPropsBuilder>(
+    _f: &impl RenderWithSlots<P>,
+) -> <P as PropsOrNoPropsBuilder>::Builder {
+    <P as PropsOrNoPropsBuilder>::builder_or_not()
+}
+
+pub fn render_view<P: Props, Marker, C: Render<P, Marker>>(component: &C, props: P) -> C::Output {
+    component.render(props)
+}
+
+pub fn render_with_slots_view<P: Props, C: RenderWithSlots<P>>(
+    component: &C,
+    children: HtmlFragment,
+    props: P,
+) -> C::Output {
+    component.render_with_slots(children, props)
+}
+
+/// What a component returns: [HtmlFragment], or a [Result] of it for fallible components.
+/// Used by [try_html](crate::try_html) and `<ErrorBoundary>`; `Marker` is either
+/// [Infallible] or [Fallible].
+pub trait IntoComponentResult<E, Marker> {
+    fn into_component_result(self) -> Result<HtmlFragment, E>;
+}
+
+pub struct Infallible;
+
+pub struct Fallible;
+
+impl<E> IntoComponentResult<E, Infallible> for HtmlFragment {
+    fn into_component_result(self) -> Result<HtmlFragment, E> {
+        Ok(self)
+    }
+}
+
+impl<E, F: Into<E>> IntoComponentResult<E, Fallible> for Result<HtmlFragment, F> {
+    fn into_component_result(self) -> Result<HtmlFragment, E> {
+        self.map_err(Into::into)
+    }
+}
+
+pub fn into_component_result<E, Marker>(
+    output: impl IntoComponentResult<E, Marker>,
+) -> Result<HtmlFragment, E> {
+    output.into_component_result()
+}
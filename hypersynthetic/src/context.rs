@@ -0,0 +1,128 @@
+//! Values provided to every component rendered inside a `<Provider>`.
+//!
+//! Components are rendered when the [html](crate::html) macro is evaluated, so a context
+//! is available from the moment `<Provider value={...}>` starts rendering its children until
+//! they are rendered. Contexts are stored per thread and looked up by type; an inner provider
+//! of the same type shadows an outer one.
+//!
+//! Async components rendered by [html_async](crate::html_async) see the contexts that were
+//! provided where they were called, even though they are polled later, possibly on another
+//! thread. That's why they only see the values provided with [provide_shared], which
+//! `<Provider>` uses inside `html_async!`.
+
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::marker::PhantomData;
+use std::ops::Range;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// A value that can be passed to an async component.
+pub(crate) type SharedContext = Arc<dyn Any + Send + Sync>;
+
+#[derive(Clone)]
+enum Context {
+    Local(Rc<dyn Any>),
+    Shared(SharedContext),
+}
+
+impl Context {
+    fn downcast_ref<T: 'static>(&self) -> Option<&T> {
+        match self {
+            Context::Local(value) => value.downcast_ref(),
+            Context::Shared(value) => value.downcast_ref(),
+        }
+    }
+}
+
+thread_local! {
+    // Every value with the id of the guard that removes it
+    static CONTEXTS: RefCell<Vec<(u64, Context)>> = const { RefCell::new(Vec::new()) };
+    static NEXT_ID: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Adds the values with new ids, which the returned guard removes.
+fn push(values: impl IntoIterator<Item = Context>) -> ContextGuard {
+    let start = NEXT_ID.get();
+    let mut id = start;
+    CONTEXTS.with(|contexts| {
+        let mut contexts = contexts.borrow_mut();
+        for value in values {
+            contexts.push((id, value));
+            id += 1;
+        }
+    });
+    NEXT_ID.set(id);
+    ContextGuard {
+        ids: start..id,
+        _not_send: PhantomData,
+    }
+}
+
+/// Makes `value` available to [use_context] until the returned guard is dropped.
+/// `<Provider>` uses it, and it can be used directly around code that renders components.
+pub fn provide<T: 'static>(value: T) -> ContextGuard {
+    push([Context::Local(Rc::new(value))])
+}
+
+/// Like [provide], and makes `value` available to the async components of
+/// [html_async](crate::html_async) as well.
+pub fn provide_shared<T: Send + Sync + 'static>(value: T) -> ContextGuard {
+    push([Context::Shared(Arc::new(value))])
+}
+
+/// Calls `f` with `value` provided as context.
+pub fn with_context<T: 'static, R>(value: T, f: impl FnOnce() -> R) -> R {
+    let _guard = provide(value);
+    f()
+}
+
+/// Returns the innermost provided value of type `T`.
+pub fn use_context<T: Clone + 'static>() -> Option<T> {
+    CONTEXTS.with(|contexts| {
+        contexts
+            .borrow()
+            .iter()
+            .rev()
+            .find_map(|(_, context)| context.downcast_ref::<T>())
+            .cloned()
+    })
+}
+
+/// All the values provided with [provide_shared] at the moment, to be restored with [enter].
+pub(crate) fn snapshot() -> Vec<SharedContext> {
+    CONTEXTS.with(|contexts| {
+        contexts
+            .borrow()
+            .iter()
+            .filter_map(|(_, context)| match context {
+                Context::Shared(value) => Some(value.clone()),
+                Context::Local(_) => None,
+            })
+            .collect()
+    })
+}
+
+/// Provides every value of a [snapshot] until the returned guard is dropped.
+pub(crate) fn enter(snapshot: &[SharedContext]) -> ContextGuard {
+    push(snapshot.iter().cloned().map(Context::Shared))
+}
+
+/// Removes the provided value when dropped, even when guards are dropped in another order
+/// than they were created in.
+#[must_use = "the context is removed as soon as the guard is dropped"]
+pub struct ContextGuard {
+    ids: Range<u64>,
+    // Contexts belong to the thread that provided them
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for ContextGuard {
+    fn drop(&mut self) {
+        CONTEXTS.with(|contexts| {
+            contexts
+                .borrow_mut()
+                .retain(|(id, _)| !self.ids.contains(id))
+        });
+    }
+}
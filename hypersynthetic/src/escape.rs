@@ -0,0 +1,613 @@
+//! Escaping for values interpolated into languages embedded in HTML.
+//!
+//! Text and attribute values are escaped with [escape_text](crate::escape_text) and
+//! [escape_attribute](crate::escape_attribute). Inside `<script>` and `<style>` the browser
+//! doesn't decode HTML entities, so the [html](crate::html) macro escapes values interpolated
+//! into them with the functions of this module instead, see [RawTextValue]. The content of
+//! comments is escaped with [escape_comment].
+//!
+//! Attribute values are escaped for HTML, but some attributes hold another language as well.
+//! The macro escapes values in them for their [AttributeContext] before escaping for HTML,
+//! unless the value is wrapped in [Trusted].
+
+use std::fmt::{Display, Write};
+
+use crate::attribute_value::split_declarations;
+use crate::{HtmlFragment, Node};
+
+/// Escapes `value` for a JavaScript string literal, quoted with `"`, `'` or `` ` ``.
+///
+/// Besides quotes and backslashes, `<`, `>` and `&` are escaped as well, so the result
+/// can't close the surrounding `<script>` element.
+///
+/// ```
+/// # use hypersynthetic::escape::escape_js_string;
+/// assert_eq!(escape_js_string("</script>"), r"\u003C/script\u003E");
+/// assert_eq!(escape_js_string("it's \"ok\""), r#"it\'s \"ok\""#);
+/// ```
+pub fn escape_js_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str(r"\\"),
+            '"' => escaped.push_str(r#"\""#),
+            '\'' => escaped.push_str(r"\'"),
+            '`' => escaped.push_str(r"\`"),
+            '\n' => escaped.push_str(r"\n"),
+            '\r' => escaped.push_str(r"\r"),
+            '\t' => escaped.push_str(r"\t"),
+            '<' | '>' | '&' | '$' | '\u{2028}' | '\u{2029}' => {
+                write!(escaped, "\\u{:04X}", c as u32).unwrap()
+            }
+            c if c.is_control() => write!(escaped, "\\u{:04X}", c as u32).unwrap(),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Encodes `value` as a JSON string with quotes, which is a JavaScript string literal as well.
+///
+/// `<`, `>`, `&` and `'` are written as `\u` escapes, so the result can't close the surrounding
+/// `<script>` element or an attribute quoted with `'`.
+///
+/// ```
+/// # use hypersynthetic::escape::json_string;
+/// assert_eq!(json_string("</script>"), r#""\u003C/script\u003E""#);
+/// assert_eq!(json_string("say \"hi\"\n"), r#""say \"hi\"\n""#);
+/// ```
+pub fn json_string(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len() + 2);
+    encoded.push('"');
+    for c in value.chars() {
+        match c {
+            '\\' => encoded.push_str(r"\\"),
+            '"' => encoded.push_str(r#"\""#),
+            '\n' => encoded.push_str(r"\n"),
+            '\r' => encoded.push_str(r"\r"),
+            '\t' => encoded.push_str(r"\t"),
+            '<' | '>' | '&' | '\'' | '\u{2028}' | '\u{2029}' => {
+                write!(encoded, "\\u{:04X}", c as u32).unwrap()
+            }
+            c if c.is_control() => write!(encoded, "\\u{:04X}", c as u32).unwrap(),
+            c => encoded.push(c),
+        }
+    }
+    encoded.push('"');
+    encoded
+}
+
+/// Escapes `value` for a CSS string literal, quoted with `"` or `'`.
+///
+/// Characters that could end the string or the surrounding `<style>` element are written
+/// as hexadecimal escapes.
+///
+/// ```
+/// # use hypersynthetic::escape::escape_css_string;
+/// assert_eq!(escape_css_string("\"</style>"), r"\22 \3C /style\3E ");
+/// ```
+pub fn escape_css_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' | '"' | '\'' | '<' | '>' | '&' => write!(escaped, "\\{:X} ", c as u32).unwrap(),
+            c if c.is_control() => write!(escaped, "\\{:X} ", c as u32).unwrap(),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Escapes `value` for a value in a CSS declaration, like `color: {value}`.
+///
+/// Letters, digits, whitespace and `#%.,-_+` are kept, so lengths, colors and keywords
+/// work as expected. Everything else, including `;`, `:` and parentheses, is written as a
+/// hexadecimal escape and can't start another declaration or a function like `url()`.
+///
+/// ```
+/// # use hypersynthetic::escape::escape_css_value;
+/// assert_eq!(escape_css_value("1px solid #fff"), "1px solid #fff");
+/// assert_eq!(escape_css_value("red; background: url(x)"), r"red\3B  background\3A  url\28 x\29 ");
+/// ```
+pub fn escape_css_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            c if c.is_alphanumeric() || c == ' ' => escaped.push(c),
+            '#' | '%' | '.' | ',' | '-' | '_' | '+' => escaped.push(c),
+            c => write!(escaped, "\\{:X} ", c as u32).unwrap(),
+        }
+    }
+    escaped
+}
+
+/// Escapes the declarations of a `style` attribute, like `color: red; margin: 0`.
+///
+/// Property names and values are escaped with [escape_css_value], so a value can't start
+/// another declaration. Declarations without a `:` are dropped.
+///
+/// ```
+/// # use hypersynthetic::escape::escape_css_declarations;
+/// assert_eq!(escape_css_declarations("color: red; margin:0 1px"), "color: red; margin: 0 1px;");
+/// assert_eq!(escape_css_declarations("top: url(x)"), r"top: url\28 x\29 ;");
+/// ```
+pub fn escape_css_declarations(style: &str) -> String {
+    let declarations: Vec<String> = split_declarations(style)
+        .into_iter()
+        .filter_map(|declaration| declaration.split_once(':'))
+        .map(|(property, value)| {
+            format!(
+                "{}: {};",
+                escape_css_value(property.trim()),
+                escape_css_value(value.trim())
+            )
+        })
+        .collect();
+    declarations.join(" ")
+}
+
+/// Escapes `text` for HTML like [escape_text](crate::escape_text), but keeps character
+/// references like `&nbsp;`, `&#x2014;` or `&copy` as they are, for text copied from HTML.
+/// Only the characters right after a `&` are looked at, so what a reference means is left to
+/// the browser, like in the HTML the text comes from.
+///
+/// ```
+/// # use hypersynthetic::escape::escape_text_keeping_references;
+/// assert_eq!(
+///     escape_text_keeping_references("Fish & Chips&nbsp;&#8212; 1 < 2"),
+///     "Fish &amp; Chips&nbsp;&#8212; 1 &lt; 2"
+/// );
+/// ```
+pub fn escape_text_keeping_references(text: &str) -> String {
+    escape_keeping_references(text, false)
+}
+
+/// Escapes an attribute value for HTML like [escape_text_keeping_references], and `"` as well.
+pub(crate) fn escape_attribute_keeping_references(value: &str) -> String {
+    escape_keeping_references(value, true)
+}
+
+fn escape_keeping_references(s: &str, is_attribute: bool) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for (i, c) in s.char_indices() {
+        match c {
+            '&' if starts_with_char_reference(&s[i + 1..]) => escaped.push('&'),
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' if is_attribute => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Whether `s`, which follows a `&`, starts like a named, decimal or hexadecimal character
+/// reference.
+fn starts_with_char_reference(s: &str) -> bool {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some('#') => match chars.next() {
+            Some('x' | 'X') => chars.next().is_some_and(|c| c.is_ascii_hexdigit()),
+            c => c.is_some_and(|c| c.is_ascii_digit()),
+        },
+        c => c.is_some_and(|c| c.is_ascii_alphanumeric()),
+    }
+}
+
+/// Escapes `text` for the content of an HTML comment, [Node::Comment].
+///
+/// Browsers don't decode entities in comments either, so a space is put between consecutive
+/// dashes instead, and in front of a leading `>` and after a trailing `-`. The result can't
+/// end the comment early and contains no `--`.
+///
+/// ```
+/// # use hypersynthetic::escape::escape_comment;
+/// assert_eq!(escape_comment(" built at 12:00 "), " built at 12:00 ");
+/// assert_eq!(escape_comment("--><script>"), "- -><script>");
+/// assert_eq!(escape_comment(">a-"), " >a- ");
+/// ```
+pub fn escape_comment(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    if text.starts_with('>') || text.starts_with("->") {
+        escaped.push(' ');
+    }
+    for c in text.chars() {
+        if c == '-' && escaped.ends_with('-') {
+            escaped.push(' ');
+        }
+        escaped.push(c);
+    }
+    if escaped.ends_with('-') {
+        escaped.push(' ');
+    }
+    escaped
+}
+
+/// Decodes numeric character references and the ones the escaping functions produce.
+pub(crate) fn unescape_attribute(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        rest = &rest[start..];
+        // Only the name right after the `&` is looked at, up to the `;` that must end it
+        let end = rest[1..]
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '#')
+            .map_or(rest.len(), |len| len + 1);
+        let decoded = rest[end..].starts_with(';').then(|| {
+            let reference = &rest[1..end];
+            let c = match reference {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                _ => reference
+                    .strip_prefix("#x")
+                    .or_else(|| reference.strip_prefix("#X"))
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .or_else(|| reference.strip_prefix('#')?.parse().ok())
+                    .and_then(char::from_u32),
+            };
+            c.map(|c| (c, end + 1))
+        });
+        match decoded.flatten() {
+            Some((c, len)) => {
+                unescaped.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                unescaped.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    unescaped.push_str(rest);
+    unescaped
+}
+
+/// URL schemes that [sanitize_url] lets through.
+const SAFE_URL_SCHEMES: &[&str] = &["http", "https", "mailto", "tel"];
+
+/// Replaces a URL with a scheme other than `http`, `https`, `mailto` and `tel`
+/// by `about:invalid`, so values like `javascript:alert(1)` can't run code when followed.
+/// Relative URLs are kept.
+///
+/// ```
+/// # use hypersynthetic::escape::sanitize_url;
+/// assert_eq!(sanitize_url("https://example.com"), "https://example.com");
+/// assert_eq!(sanitize_url("/search?q=a:b"), "/search?q=a:b");
+/// assert_eq!(sanitize_url(" JavaScript:alert(1)"), "about:invalid");
+/// ```
+pub fn sanitize_url(url: &str) -> &str {
+    // Browsers ignore leading whitespace and control characters,
+    // and tabs and line breaks anywhere in the URL
+    let mut scheme = String::new();
+    for c in url.trim_start_matches(|c: char| c <= ' ').chars() {
+        match c {
+            '\t' | '\n' | '\r' => {}
+            ':' if SAFE_URL_SCHEMES
+                .iter()
+                .any(|safe| scheme.eq_ignore_ascii_case(safe)) =>
+            {
+                return url;
+            }
+            ':' => return "about:invalid",
+            '/' | '?' | '#' => break,
+            c => scheme.push(c),
+        }
+    }
+    url
+}
+
+/// What an attribute value holds besides text, which decides how the [html](crate::html)
+/// macro escapes values in it:
+///
+/// - [Url](AttributeContext::Url) for attributes like `href`, `src` and `action`. A value that
+///   can decide the scheme of the URL is checked with [sanitize_url].
+/// - [JavaScript](AttributeContext::JavaScript) for event handlers like `onclick`. Values
+///   interpolated into the attribute are escaped with [escape_js_string], so they belong in a
+///   string literal. Code can't be escaped, so a value that is the whole attribute becomes a
+///   quoted string literal, and code has to be [Trusted].
+/// - [Css](AttributeContext::Css) for `style`. A value that is the whole attribute is split
+///   into declarations with [escape_css_declarations], interpolated values are escaped with
+///   [escape_css_value].
+/// - [CssValue](AttributeContext::CssValue) for `style:` directives, whose values are escaped
+///   with [escape_css_value].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AttributeContext {
+    Plain,
+    Url,
+    JavaScript,
+    Css,
+    CssValue,
+}
+
+impl AttributeContext {
+    /// Classifies an attribute by its name, which is compared case-insensitively.
+    ///
+    /// ```
+    /// # use hypersynthetic::escape::AttributeContext;
+    /// assert_eq!(AttributeContext::of_attribute("HREF"), AttributeContext::Url);
+    /// assert_eq!(AttributeContext::of_attribute("onClick"), AttributeContext::JavaScript);
+    /// assert_eq!(AttributeContext::of_attribute("hx-on:click"), AttributeContext::JavaScript);
+    /// assert_eq!(AttributeContext::of_attribute("title"), AttributeContext::Plain);
+    /// assert_eq!(AttributeContext::of_attribute("only"), AttributeContext::Plain);
+    /// ```
+    pub fn of_attribute(name: &str) -> Self {
+        let name = name.to_lowercase();
+        if URL_ATTRIBUTES.contains(&name.as_str()) {
+            AttributeContext::Url
+        } else if EVENT_HANDLER_ATTRIBUTES.contains(&name.as_str()) || name.starts_with("hx-on") {
+            AttributeContext::JavaScript
+        } else if name == "style" {
+            AttributeContext::Css
+        } else {
+            AttributeContext::Plain
+        }
+    }
+
+    /// Escapes a value that is the whole attribute, like `href={value}`.
+    pub fn escape_value(self, value: &str) -> String {
+        match self {
+            AttributeContext::Plain => value.to_owned(),
+            AttributeContext::Url => sanitize_url(value).to_owned(),
+            AttributeContext::JavaScript => format!("\"{}\"", escape_js_string(value)),
+            AttributeContext::Css => escape_css_declarations(value),
+            AttributeContext::CssValue => escape_css_value(value),
+        }
+    }
+
+    /// Escapes a value interpolated into an attribute, like `onclick="greet('{value}')"`.
+    /// URLs are checked by the macro once they are complete.
+    pub fn escape_segment(self, value: &str) -> String {
+        match self {
+            AttributeContext::Plain | AttributeContext::Url => value.to_owned(),
+            AttributeContext::JavaScript => escape_js_string(value),
+            AttributeContext::Css | AttributeContext::CssValue => escape_css_value(value),
+        }
+    }
+}
+
+/// Attributes from the HTML standard whose value is a single URL.
+const URL_ATTRIBUTES: &[&str] = &[
+    "action",
+    "background",
+    "cite",
+    "codebase",
+    "data",
+    "formaction",
+    "href",
+    "longdesc",
+    "manifest",
+    "poster",
+    "src",
+    "xlink:href",
+];
+
+/// Event handler attributes from the HTML standard and the specifications it builds on.
+const EVENT_HANDLER_ATTRIBUTES: &[&str] = &[
+    "onabort",
+    "onafterprint",
+    "onanimationcancel",
+    "onanimationend",
+    "onanimationiteration",
+    "onanimationstart",
+    "onauxclick",
+    "onbeforeinput",
+    "onbeforematch",
+    "onbeforeprint",
+    "onbeforetoggle",
+    "onbeforeunload",
+    "onbegin",
+    "onblur",
+    "oncancel",
+    "oncanplay",
+    "oncanplaythrough",
+    "onchange",
+    "onclick",
+    "onclose",
+    "oncommand",
+    "oncontextlost",
+    "oncontextmenu",
+    "oncontextrestored",
+    "oncopy",
+    "oncuechange",
+    "oncut",
+    "ondblclick",
+    "ondrag",
+    "ondragend",
+    "ondragenter",
+    "ondragexit",
+    "ondragleave",
+    "ondragover",
+    "ondragstart",
+    "ondrop",
+    "ondurationchange",
+    "onemptied",
+    "onend",
+    "onended",
+    "onerror",
+    "onfocus",
+    "onfocusin",
+    "onfocusout",
+    "onformdata",
+    "onfullscreenchange",
+    "onfullscreenerror",
+    "ongotpointercapture",
+    "onhashchange",
+    "oninput",
+    "oninvalid",
+    "onkeydown",
+    "onkeypress",
+    "onkeyup",
+    "onlanguagechange",
+    "onload",
+    "onloadeddata",
+    "onloadedmetadata",
+    "onloadend",
+    "onloadstart",
+    "onlostpointercapture",
+    "onmessage",
+    "onmessageerror",
+    "onmousedown",
+    "onmouseenter",
+    "onmouseleave",
+    "onmousemove",
+    "onmouseout",
+    "onmouseover",
+    "onmouseup",
+    "onmousewheel",
+    "onoffline",
+    "ononline",
+    "onpagehide",
+    "onpagereveal",
+    "onpageshow",
+    "onpageswap",
+    "onpaste",
+    "onpause",
+    "onplay",
+    "onplaying",
+    "onpointercancel",
+    "onpointerdown",
+    "onpointerenter",
+    "onpointerleave",
+    "onpointermove",
+    "onpointerout",
+    "onpointerover",
+    "onpointerrawupdate",
+    "onpointerup",
+    "onpopstate",
+    "onprogress",
+    "onratechange",
+    "onreadystatechange",
+    "onrejectionhandled",
+    "onrepeat",
+    "onreset",
+    "onresize",
+    "onscroll",
+    "onscrollend",
+    "onsearch",
+    "onsecuritypolicyviolation",
+    "onseeked",
+    "onseeking",
+    "onselect",
+    "onselectionchange",
+    "onselectstart",
+    "onshow",
+    "onslotchange",
+    "onstalled",
+    "onstorage",
+    "onsubmit",
+    "onsuspend",
+    "ontimeupdate",
+    "ontoggle",
+    "ontouchcancel",
+    "ontouchend",
+    "ontouchmove",
+    "ontouchstart",
+    "ontransitioncancel",
+    "ontransitionend",
+    "ontransitionrun",
+    "ontransitionstart",
+    "onunhandledrejection",
+    "onunload",
+    "onvisibilitychange",
+    "onvolumechange",
+    "onwaiting",
+    "onwebkitanimationend",
+    "onwebkitanimationiteration",
+    "onwebkitanimationstart",
+    "onwebkittransitionend",
+    "onwheel",
+];
+
+/// Marks an attribute value as safe for its [AttributeContext], so it is only escaped for HTML.
+///
+/// ```
+/// # use hypersynthetic::html;
+/// use hypersynthetic::escape::Trusted;
+///
+/// let (url, handler) = ("javascript:void(0)", "history.back()");
+/// let link = html! {
+///     <a href={Trusted(url)} onclick={Trusted(handler)}>"Back"</a>
+/// };
+/// assert_eq!(
+///     link.to_string(),
+///     r#"<a href="javascript:void(0)" onclick="history.back()">Back</a>"#
+/// );
+/// ```
+///
+/// It intentionally doesn't implement [Display], so it can't end up
+/// in a place where it would be escaped anyway.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Trusted<T>(pub T);
+
+/// The value of a `{{expression}}` in the [html](crate::html) macro, which is inserted without
+/// escaping. An [HtmlFragment] is inserted as its nodes, anything else that implements
+/// [Display] as text. The macro picks the right conversion with
+/// autoref-based dispatch.
+pub struct Unescaped<'a, T: ?Sized>(pub &'a T);
+
+pub trait FragmentNodes {
+    fn to_nodes(&self) -> Vec<Node>;
+}
+
+impl FragmentNodes for Unescaped<'_, HtmlFragment> {
+    fn to_nodes(&self) -> Vec<Node> {
+        self.0.get_nodes()
+    }
+}
+
+impl FragmentNodes for Unescaped<'_, &HtmlFragment> {
+    fn to_nodes(&self) -> Vec<Node> {
+        self.0.get_nodes()
+    }
+}
+
+pub trait DisplayNodes {
+    fn to_nodes(&self) -> Vec<Node>;
+}
+
+impl<T: Display + ?Sized> DisplayNodes for &Unescaped<'_, T> {
+    fn to_nodes(&self) -> Vec<Node> {
+        vec![Node::Text(self.0.to_string())]
+    }
+}
+
+/// The value of a `{{expression}}` in the code of a `<script>` or `<style>` element, outside of
+/// its string literals. A [Trusted] value is inserted as is, anything else that implements
+/// [Display] is encoded with [json_string] in a script and escaped with [escape_css_value]
+/// in a stylesheet. The macro picks the right conversion with autoref-based dispatch.
+pub struct RawTextValue<'a, T: ?Sized>(pub &'a T);
+
+pub trait TrustedRawTextValue {
+    fn to_js_value(&self) -> String;
+    fn to_css_value(&self) -> String;
+}
+
+impl<T: Display> TrustedRawTextValue for RawTextValue<'_, Trusted<T>> {
+    fn to_js_value(&self) -> String {
+        self.0.0.to_string()
+    }
+
+    fn to_css_value(&self) -> String {
+        self.0.0.to_string()
+    }
+}
+
+pub trait DisplayRawTextValue {
+    fn to_js_value(&self) -> String;
+    fn to_css_value(&self) -> String;
+}
+
+impl<T: Display + ?Sized> DisplayRawTextValue for &RawTextValue<'_, T> {
+    fn to_js_value(&self) -> String {
+        json_string(&self.0.to_string())
+    }
+
+    fn to_css_value(&self) -> String {
+        escape_css_value(&self.0.to_string())
+    }
+}
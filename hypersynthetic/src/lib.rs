@@ -63,7 +63,15 @@ pub use htmlize::{escape_attribute, escape_text};
 pub use typed_builder;
 pub use typed_builder_macro;
 
+pub mod async_render;
+pub mod attribute_value;
 pub mod component;
+pub mod context;
+pub mod escape;
+pub mod parse;
+pub mod pretty;
+pub mod select;
+pub mod visit;
 
 /// The component macro provides a way to define reusable and self-contained web components.
 /// A component is a function that returns a [HtmlFragment]. The easiest way to create one is
@@ -104,6 +112,107 @@ pub mod component;
 /// When calling a component from [html] macro, the properties are passed as html arguments.
 /// The arguments can be in any order, it's not necessary for them to be in the same order as defined in the component function.
 ///
+/// ## Prop options
+///
+/// By default every prop has to be passed. A parameter can be annotated with `#[prop(...)]`
+/// to change that:
+///
+/// - `#[prop(default)]` uses [Default::default] when the prop is not passed.
+/// - `#[prop(default = expr)]` uses `expr` when the prop is not passed.
+/// - `#[prop(optional)]` on an [Option] makes it `None` when the prop is not passed
+///   and lets the caller pass the value without wrapping it in `Some`.
+/// - `#[prop(into)]` accepts anything that converts into the parameter type with [Into].
+///
+/// ```
+/// # use hypersynthetic::prelude::*;
+/// #[component]
+/// fn Button(
+///     #[prop(into)] label: String,
+///     #[prop(default = "primary")] variant: &str,
+///     #[prop(default)] disabled: bool,
+///     #[prop(optional)] title: Option<&str>,
+/// ) -> HtmlFragment {
+///     html! {
+///         <button class={variant} title={title.unwrap_or_default()} :if={!disabled}>{label}</button>
+///     }
+/// }
+///
+/// # fn main() {
+/// let result = html! {
+///     <Button label="Save" />
+///     <Button label="Delete" variant="danger" title="Careful" />
+/// };
+///
+/// assert_eq!(
+///     result.to_string(),
+///     r#"<button class="primary" title="">Save</button><button class="danger" title="Careful">Delete</button>"#
+/// );
+/// # }
+/// ```
+///
+/// ## Rest attributes
+///
+/// Attributes that are not props are a compile error, unless the component is declared with
+/// `#[component(rest_attrs)]` and has a parameter of type [Attributes]. Then they are collected
+/// into that parameter, which can be spread onto an element with `{..attrs}`. Spread `class`
+/// and `style` attributes are merged with the ones of the element.
+/// Like values of expressions, rest attributes are escaped for their
+/// [context](#context-aware-escaping) when they are spread, so an event handler is passed as
+/// `onclick={Trusted("go()")}`.
+///
+/// ```
+/// # use hypersynthetic::prelude::*;
+/// #[component(rest_attrs)]
+/// fn Button(label: &str, attrs: Attributes) -> HtmlFragment {
+///     html! {
+///         <button class="btn" {..attrs}>{label}</button>
+///     }
+/// }
+///
+/// # fn main() {
+/// let result = html! {
+///     <Button class="x" hx-post="/save" label="Go" />
+/// };
+///
+/// assert_eq!(result.to_string(), r#"<button class="btn x" hx-post="/save">Go</button>"#);
+/// # }
+/// ```
+///
+/// ## Generic components
+///
+/// Components can have type and const parameters, and `impl Trait` arguments. Type
+/// parameters are inferred from the props when possible; otherwise they are passed with
+/// a turbofish, which the closing tag doesn't need to repeat.
+///
+/// ```
+/// # use hypersynthetic::prelude::*;
+/// # use std::fmt::Display;
+/// #[component]
+/// fn Cells<T: Display, const N: usize>(children: HtmlFragment, value: T) -> HtmlFragment {
+///     html! {
+///         <tr><td :for={_ in 0..N}>{&value}</td>{{ children }}</tr>
+///     }
+/// }
+///
+/// # fn main() {
+/// let row = html! {
+///     <Cells::<_, 2> value={7}><td>"end"</td></Cells>
+/// };
+///
+/// assert_eq!(row.to_string(), "<tr><td>7</td><td>7</td><td>end</td></tr>");
+/// # }
+/// ```
+///
+/// ## Fallible components
+///
+/// A component can return `Result<HtmlFragment, E>`. Fallible components can be called from
+/// the [try_html] macro, or inside an `<ErrorBoundary>`.
+///
+/// ## Async components
+///
+/// A component can be an `async fn`. Async components can only be called from the
+/// [html_async] macro, which renders them concurrently.
+///
 /// # Slots
 ///
 /// Components in this library can accept a slot argument, which allows for flexible and reusable HTML structures.
@@ -146,6 +255,41 @@ pub mod component;
 /// that will be injected into the `<div>` element.
 /// The double curly braces `{{ }}` are used to disable HTML escaping,
 /// which is the desired behavior in most cases to ensure the HTML content is rendered correctly.
+///
+/// ## Named slots
+///
+/// Every other [HtmlFragment] parameter is a named slot. It is filled with `<:name>...</:name>`
+/// or with a child element that has a `slot="name"` attribute. Slots that are not filled,
+/// including the default one, get an empty [HtmlFragment].
+///
+/// ```
+/// # use hypersynthetic::prelude::*;
+/// #[component]
+/// fn Card(body: HtmlFragment, header: HtmlFragment, footer: HtmlFragment) -> HtmlFragment {
+///     html! {
+///         <div class="card">
+///             <header>{{ header }}</header>
+///             <main>{{ body }}</main>
+///             <footer>{{ footer }}</footer>
+///         </div>
+///     }
+/// }
+///
+/// # fn main() {
+/// let result = html! {
+///     <Card>
+///         <:header><h1>"Title"</h1></:header>
+///         <p>"Body"</p>
+///         <small slot="footer">"Fine print"</small>
+///     </Card>
+/// };
+///
+/// assert_eq!(
+///     result.to_string(),
+///     "<div class=\"card\"><header><h1>Title</h1></header><main><p>Body</p></main><footer><small>Fine print</small></footer></div>"
+/// );
+/// # }
+/// ```
 pub use hypersynthetic_macros::component;
 
 /// The `html` macro allows to construct html fragments in Rust.
@@ -153,10 +297,25 @@ pub use hypersynthetic_macros::component;
 ///
 /// 1. Void tags need to be self-closing. For example `<br>` should be written as `<br />`.
 ///
-/// 2. Bare text is not (yet) allowed. Instead you should put a string literal inside an expression
-///    like `<span>{"text"}</span>`. Alternatively, for convenience, you can use the string literal
-///    directly like `<span>"text"</span>`. But the latter syntax is likely to change in the future.
-///    See this [github issue](https://github.com/sanchopanca/hypersynthetic/issues/3) for details.
+/// 2. Bare text has to consist of valid Rust tokens. For example, unbalanced quotes and brackets
+///    are not allowed, and `//` starts a comment. When that gets in the way, put the text in a
+///    string literal: `<span>"text"</span>`.
+///
+/// ```
+/// # use hypersynthetic::html;
+/// let name = "Ferris";
+/// let p = html! {
+///     <p>
+///         Hello, <b>{name}</b>!
+///         Fish &amp; Chips
+///     </p>
+/// };
+/// assert_eq!(p.to_string(), "<p>Hello, <b>Ferris</b>! Fish &amp; Chips</p>");
+/// ```
+///
+/// Whitespace in bare text is collapsed into single spaces. Whitespace between text and tags
+/// or expressions is kept as a single space unless it contains a line break. Character references
+/// like `&nbsp;` are kept as is, everything else is escaped.
 ///
 /// With gotchas out the way, here are the features:
 ///
@@ -218,8 +377,82 @@ pub use hypersynthetic_macros::component;
 /// assert_eq!(div.to_string(), "<div>Hello World</div>");
 /// ```
 ///
+/// ## Optional and boolean attributes
+/// If the value of an attribute is an `Option`, `None` omits the attribute.
+/// Boolean attributes from the HTML standard, like `disabled` or `checked`, take a `bool`
+/// and are rendered without a value when it's `true`. Any other attribute can be toggled
+/// the same way with the `name:if={condition}` form. `hidden` is toggled by a `bool` or an
+/// `Option<bool>`, and any other value, like `"until-found"`, is rendered as its value.
+/// ```
+/// # use hypersynthetic::html;
+/// let tooltip: Option<&str> = None;
+/// let is_disabled = true;
+/// let boosted = false;
+/// let button = html! {
+///     <button title={tooltip} disabled={is_disabled} hx-boost:if={boosted}>"Save"</button>
+/// };
+/// assert_eq!(button.to_string(), "<button disabled>Save</button>");
+/// ```
+///
+/// ## Class and style directives
+/// `class:name={condition}` adds a class when `condition` is true, and `style:property={value}`
+/// sets a CSS property (an `Option` value that is `None` sets nothing). They are merged with
+/// the `class` and `style` attributes of the same element into a single attribute without
+/// duplicate classes or properties.
+/// ```
+/// # use hypersynthetic::html;
+/// let is_active = true;
+/// let color = "red";
+/// let li = html! {
+///     <li class="item" class:active={is_active} style:color={color}>"Home"</li>
+/// };
+/// assert_eq!(li.to_string(), r#"<li class="item active" style="color: red;">Home</li>"#);
+/// ```
+///
+/// ## Spread attributes
+/// `{..expression}` adds the attributes of an [Attributes] set, or of anything that iterates
+/// over `(name, value)` pairs, like a `HashMap`. Names and values are escaped, values for their
+/// [context](#context-aware-escaping) as well unless they are set with
+/// [Attributes::set_trusted], and a spread attribute replaces an earlier attribute with the
+/// same name.
+/// ```
+/// # use hypersynthetic::{html, Attributes};
+/// let mut attrs = Attributes::new();
+/// attrs.set("id", "main");
+/// attrs.set_flag("hidden");
+/// let extra = [("data-page", "home")];
+/// let div = html! {
+///     <div id="default" {..attrs} {..extra}></div>
+/// };
+/// assert_eq!(div.to_string(), r#"<div id="main" hidden data-page="home"></div>"#);
+/// ```
+///
+/// ## Context-aware escaping
+/// Values of expressions in URL attributes like `href` and `src`, event handlers like `onclick`,
+/// and `style` are escaped for that language before they are escaped for HTML, as described in
+/// [AttributeContext](escape::AttributeContext). Literal values are kept as they are, and
+/// values wrapped in [Trusted](escape::Trusted) are only escaped for HTML. An expression that
+/// is the whole value of an event handler, like `onclick={handler}`, becomes a quoted string
+/// literal, so code has to be `Trusted`. Attributes are classified by their name at runtime,
+/// so the attributes of spreads, rest attributes and names like `{name}={value}` are escaped
+/// the same way.
+/// ```
+/// # use hypersynthetic::html;
+/// let url = "javascript:alert(1)";
+/// let name = "'); alert('";
+/// let color = "red; display: none";
+/// let a = html! {
+///     <a href={url} onclick="greet('{name}')" style="color: {color}">"Hi"</a>
+/// };
+/// assert_eq!(
+///     a.to_string(),
+///     r#"<a href="about:invalid" onclick="greet('\'); alert(\'')" style="color: red\3B  display\3A  none">Hi</a>"#
+/// );
+/// ```
+///
 /// ## Disabling escaping
-/// To disable escaping, use double curly braces: `{{expression}}`.
+/// To disable escaping, use double curly braces: `{{expression}}`. An [HtmlFragment], like
+/// the `children` of a component, is inserted as its nodes.
 /// ```
 /// # use hypersynthetic::html;
 /// let txt = "<span>I know what I'm doing</span>";
@@ -240,6 +473,69 @@ pub use hypersynthetic_macros::component;
 /// assert_eq!(div.to_string(), "<div>{txt} World</div>");
 /// ```
 ///
+/// # Scripts and styles
+/// The content of `<script>` and `<style>` is written as is, without quotes and without
+/// HTML escaping. Line breaks between tokens are kept, other whitespace becomes a single space.
+/// Braces belong to the script, so `{expression}` is only interpolated inside string literals,
+/// where the value is escaped for a JavaScript or CSS string with the functions of [escape].
+/// Elsewhere, `{{expression}}` inserts a value: a JSON string in a script and a value escaped
+/// with [escape_css_value](escape::escape_css_value) in a stylesheet, unless it is
+/// [Trusted](escape::Trusted). A lone `{name}` is a compile error, since it would be written
+/// out as is.
+/// ```
+/// # use hypersynthetic::html;
+/// let name = "</script>";
+/// let script = html! {
+///     <script>
+///         if (1 < 2) { alert("Hi, {name}") }
+///     </script>
+/// };
+/// assert_eq!(
+///     script.to_string(),
+///     r#"<script>if (1 < 2) { alert("Hi, \u003C/script\u003E") }</script>"#
+/// );
+///
+/// let (user, color) = ("Bob", "red");
+/// let page = html! {
+///     <script>const user = {{user}};</script>
+///     <style>p { color: {{color}}; }</style>
+/// };
+/// assert_eq!(
+///     page.to_string(),
+///     r#"<script>const user = "Bob";</script><style>p { color: red; }</style>"#
+/// );
+/// ```
+/// Code that Rust can't tokenize, like single-quoted strings or `1.5em`, can be written
+/// as a single string literal, whose value becomes the content. Like in other string literals,
+/// `{{` and `}}` stand for braces there, and `{expression}` is interpolated. Inside the quotes
+/// of the script or stylesheet it is escaped for a string, elsewhere it is inserted like
+/// `{{expression}}` above.
+/// ```
+/// # use hypersynthetic::html;
+/// let style = html! {
+///     <style>"p {{ margin: 1.5em; font-family: 'Fira Sans'; }}"</style>
+/// };
+/// assert_eq!(style.to_string(), "<style>p { margin: 1.5em; font-family: 'Fira Sans'; }</style>");
+/// ```
+///
+/// # Comments
+/// `<!-- ... -->` renders an HTML comment, stored as a [Node::Comment]. Like bare text, the
+/// content can be string literals, `{expression}` and other tokens. Whitespace between them
+/// becomes a single space, and the content is escaped with
+/// [escape_comment](escape::escape_comment), so a value can't end the comment.
+/// ```
+/// # use hypersynthetic::html;
+/// let version = "1.2 --> 1.3";
+/// let page = html! {
+///     <!-- "SPDX-License-Identifier: MIT" -->
+///     <p>"Hi"<!-- version {version} --></p>
+/// };
+/// assert_eq!(
+///     page.to_string(),
+///     "<!-- SPDX-License-Identifier: MIT --><p>Hi<!-- version 1.2 - -> 1.3 --></p>"
+/// );
+/// ```
+///
 /// # Conditionals
 /// A special pseudo-attribute `:if` is used to conditionally render an element.
 /// ```
@@ -261,19 +557,42 @@ pub use hypersynthetic_macros::component;
 /// assert_eq!(div.to_string(), "");
 /// ```
 ///
-/// To emulate `else` branch, reverse the condition:
+/// The element or component right after it can have an `:else-if` or `:else` pseudo-attribute.
+/// The whole chain is evaluated as a single `if / else if / else`, so every condition is evaluated
+/// at most once. `:else-if` and `:else` without a preceding `:if` are a compile error.
 /// ```
 /// # use hypersynthetic::html;
-/// let notify_user = false;
+/// let temperature = 15;
 /// let div = html! {
-///     <div :if={notify_user}>
-///         <span>"Be careful"</span>
-///     </div>
-///     <div :if={!notify_user}>
-///         <span>"You are safe"</span>
-///     </div>
+///     <span :if={temperature > 25}>"Hot"</span>
+///     <span :else-if={temperature > 10}>"Mild"</span>
+///     <span :else>"Cold"</span>
 /// };
-/// assert_eq!(div.to_string(), "<div><span>You are safe</span></div>");
+/// assert_eq!(div.to_string(), "<span>Mild</span>");
+/// ```
+///
+/// # Matching
+/// A `<template>` with a `:match` pseudo-attribute renders the first of its children whose `:case`
+/// pattern matches. It generates a regular Rust `match`, so patterns can bind variables, have
+/// guards, and must be exhaustive. The `<template>` tag itself is not rendered.
+/// ```
+/// # use hypersynthetic::html;
+/// enum State {
+///     Loading,
+///     Loaded(u32),
+///     Failed(&'static str),
+/// }
+///
+/// let state = State::Loaded(3);
+/// let div = html! {
+///     <template :match={state}>
+///         <span :case={State::Loading}>"Loading..."</span>
+///         <span :case={State::Loaded(count) if count > 1}>"{count} items"</span>
+///         <span :case={State::Loaded(_)}>"One item"</span>
+///         <span :case={State::Failed(error)} class="error">{error}</span>
+///     </template>
+/// };
+/// assert_eq!(div.to_string(), "<span>3 items</span>");
 /// ```
 ///
 /// # Iteration
@@ -294,6 +613,66 @@ pub use hypersynthetic_macros::component;
 /// assert_eq!(div.to_string(), r#"<input type="text" value="1" /><input type="text" value="2" />"#);
 /// ```
 ///
+/// ## Loop information
+/// `:for={pat in collection, info}` binds a [LoopInfo] to `info` in every iteration,
+/// with its index and whether it is the first, last, even or odd one.
+/// ```
+/// # use hypersynthetic::html;
+/// let items = ["a", "b", "c"];
+/// let list = html! {
+///     <li :for={item in items, info} class:last={info.last}>{info.index}": "{item}</li>
+/// };
+/// assert_eq!(list.to_string(), r#"<li>0: a</li><li>1: b</li><li class="last">2: c</li>"#);
+/// ```
+///
+/// ## Empty collections
+/// An element or component with `:empty` right after one with `:for` is rendered
+/// when the collection yields nothing.
+/// ```
+/// # use hypersynthetic::html;
+/// let items: Vec<&str> = Vec::new();
+/// let list = html! {
+///     <ul>
+///         <li :for={item in &items}>{item}</li>
+///         <li :empty>"Nothing here"</li>
+///     </ul>
+/// };
+/// assert_eq!(list.to_string(), "<ul><li>Nothing here</li></ul>");
+/// ```
+///
+/// # Local bindings
+/// `:let={pat = expression}` evaluates the expression once and binds it for the attributes
+/// and children of the node. A node can have several `:let`, and each one can use the
+/// bindings before it. With `:for` they are evaluated in every iteration, and with `:if`
+/// only when the condition holds.
+/// ```
+/// # use hypersynthetic::html;
+/// let (first, last) = ("Ada", "Lovelace");
+/// let card = html! {
+///     <div :let={name = format!("{first} {last}")} title={name}>
+///         <h1>{name}</h1>
+///     </div>
+/// };
+/// assert_eq!(card.to_string(), r#"<div title="Ada Lovelace"><h1>Ada Lovelace</h1></div>"#);
+/// ```
+///
+/// # Fragments
+/// `<>...</>` groups several nodes without rendering a wrapper element. To use `:if`, `:else-if`,
+/// `:else`, `:for`, `:let` or `:case` on a group, write it as `<Fragment>` instead.
+/// ```
+/// # use hypersynthetic::html;
+/// let terms = [("HTML", "Markup"), ("CSS", "Styles")];
+/// let dl = html! {
+///     <dl>
+///         <Fragment :for={(term, definition) in terms}>
+///             <dt>{term}</dt>
+///             <dd>{definition}</dd>
+///         </Fragment>
+///     </dl>
+/// };
+/// assert_eq!(dl.to_string(), "<dl><dt>HTML</dt><dd>Markup</dd><dt>CSS</dt><dd>Styles</dd></dl>");
+/// ```
+///
 /// # Components
 /// Components can be called as tags. Here is an example:
 /// ```
@@ -313,18 +692,154 @@ pub use hypersynthetic_macros::component;
 /// }
 /// ```
 /// See [component] macro for more details.
+///
+/// # Context
+/// `<Provider value={expression}>` makes a value available to every component rendered
+/// inside it, which can read it with [use_context](context::use_context) instead of
+/// receiving it through props. See the [context] module for details.
+/// ```
+/// # use hypersynthetic::prelude::*;
+/// #[derive(Clone)]
+/// struct Locale(&'static str);
+///
+/// #[component]
+/// fn Greeting() -> HtmlFragment {
+///     let locale = use_context::<Locale>().map_or("en", |locale| locale.0);
+///     html! {
+///         <p lang={locale}>"Hi"</p>
+///     }
+/// }
+///
+/// # fn main() {
+/// let page = html! {
+///     <Provider value={Locale("fr")}>
+///         <Greeting />
+///     </Provider>
+///     <Greeting />
+/// };
+/// assert_eq!(page.to_string(), r#"<p lang="fr">Hi</p><p lang="en">Hi</p>"#);
+/// # }
+/// ```
+///
+/// # Static markup
+/// Children of an element that contain no expressions, components, `:if` or `:for` are
+/// rendered at compile time and written out as one string, so building them at runtime costs
+/// almost nothing. They are still searched by [HtmlFragment::select] and traversed by the
+/// [visit] module, and [HtmlFragment::expand_markup] turns them into elements that can be
+/// modified.
+/// ```
+/// # use hypersynthetic::html;
+/// let footer = html! {
+///     <footer><p>"Made with "<a href="https://www.rust-lang.org">"Rust"</a></p></footer>
+/// };
+/// assert_eq!(
+///     footer.to_string(),
+///     r#"<footer><p>Made with <a href="https://www.rust-lang.org">Rust</a></p></footer>"#
+/// );
+/// assert_eq!(footer.select("footer a").count(), 1);
+/// ```
 pub use hypersynthetic_macros::html;
 
+/// The same as [html], but components may be `async`. Returns a
+/// [RenderFuture](async_render::RenderFuture) that renders all async components
+/// concurrently, so it has to be awaited.
+///
+/// The markup around the components is built when the macro is evaluated; only the components
+/// themselves run later, with the [context] values that were provided where they were called
+/// by `<Provider>` or [provide_shared](context::provide_shared), which have to be [Send] and
+/// [Sync]. Async components have to be [Send]. Components that inspect their children don't
+/// see the nodes of async components among them, which are only rendered later.
+/// ```
+/// # use hypersynthetic::prelude::*;
+/// #[component]
+/// async fn UserName(id: u32) -> HtmlFragment {
+///     let name = async { format!("user{id}") }.await;
+///     html! {
+///         <b>{name}</b>
+///     }
+/// }
+///
+/// # fn main() {
+/// # let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+/// # rt.block_on(async {
+/// let list = html_async! {
+///     <ul>
+///         <li :for={id in 1..=2}><UserName id={id} /></li>
+///     </ul>
+/// }
+/// .await;
+/// assert_eq!(list.to_string(), "<ul><li><b>user1</b></li><li><b>user2</b></li></ul>");
+/// # });
+/// # }
+/// ```
+pub use hypersynthetic_macros::html_async;
+
+/// The same as [html], but components may return `Result<HtmlFragment, E>`.
+/// Returns `Result<HtmlFragment, E>` with the first error, converted with [Into].
+/// `?` and `return` can be used in expressions as well, and return from the enclosing
+/// function like anywhere else in it.
+///
+/// The error type has to be known from the context, for example from the return type
+/// of the enclosing function.
+/// ```
+/// # use hypersynthetic::prelude::*;
+/// #[component]
+/// fn Age(input: &str) -> Result<HtmlFragment, std::num::ParseIntError> {
+///     let age: u32 = input.parse()?;
+///     Ok(html! { <span>{age}</span> })
+/// }
+///
+/// fn render(input: &str) -> Result<HtmlFragment, Box<dyn std::error::Error>> {
+///     try_html! {
+///         <p><Age input={input} /></p>
+///     }
+/// }
+///
+/// # fn main() {
+/// assert_eq!(render("42").unwrap().to_string(), "<p><span>42</span></p>");
+/// assert!(render("forty-two").is_err());
+/// # }
+/// ```
+///
+/// # Error boundaries
+/// `<ErrorBoundary fallback={function}>` works in both [html] and [try_html]. Components
+/// inside it may fail as well; when one does, the boundary renders what `function` returns
+/// for the error instead of its children. Only component errors are caught; `?` in an
+/// expression still returns from the enclosing function.
+/// ```
+/// # use hypersynthetic::prelude::*;
+/// # #[component]
+/// # fn Age(input: &str) -> Result<HtmlFragment, std::num::ParseIntError> {
+/// #     let age: u32 = input.parse()?;
+/// #     Ok(html! { <span>{age}</span> })
+/// # }
+/// # fn main() {
+/// let fallback = |error: std::num::ParseIntError| html! { <em>{error}</em> };
+/// let page = html! {
+///     <ErrorBoundary fallback={fallback}>
+///         <Age input="many" />
+///     </ErrorBoundary>
+/// };
+/// assert_eq!(page.to_string(), "<em>invalid digit found in string</em>");
+/// # }
+/// ```
+pub use hypersynthetic_macros::try_html;
+
 pub mod prelude {
-    pub use crate::HtmlFragment;
     pub use crate::component::{Component, Props, component_props_builder, component_view};
+    pub use crate::context::use_context;
     pub use crate::typed_builder;
-    pub use crate::{component, html};
+    pub use crate::{Attributes, HtmlFragment};
+    pub use crate::{component, html, html_async, try_html};
 }
 
 use std::fmt;
+use std::io;
+use std::iter::FlatMap;
 use std::slice::Iter;
 use std::slice::IterMut;
+use std::str::FromStr;
+use std::sync::LazyLock;
 
 #[derive(Clone, Debug)]
 pub enum HtmlFragment {
@@ -332,10 +847,22 @@ pub enum HtmlFragment {
 }
 
 #[derive(Clone, Debug)]
+#[non_exhaustive]
 pub enum Node {
     Element(ElementData),
     Text(String),
     DocType,
+    /// The content of an HTML comment, between `<!--` and `-->`. It is written out verbatim,
+    /// so it has to be escaped with [escape_comment](escape::escape_comment).
+    Comment(String),
+    /// Markup pre-rendered at compile time by the [html] macro. It is written out verbatim.
+    /// See [HtmlFragment::expand_markup].
+    #[doc(hidden)]
+    Static(&'static StaticMarkup),
+    /// Where an async component is rendered once the future of [html_async] is awaited.
+    /// It renders nothing until then.
+    #[doc(hidden)]
+    Pending(usize),
 }
 
 #[derive(Clone, Debug)]
@@ -346,12 +873,22 @@ pub struct ElementData {
     pub self_closing: bool,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Attribute {
     pub name: String,
     pub value: Option<String>,
 }
 
+/// Children rendered at compile time by the [html] macro, together with the nodes they are
+/// rendered from. The nodes are built the first time they are needed, for example by
+/// [HtmlFragment::select], and kept for later.
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct StaticMarkup {
+    html: &'static str,
+    nodes: LazyLock<HtmlFragment>,
+}
+
 impl HtmlFragment {
     // TODO: consider something else except Vec
     pub fn new(nodes: Vec<Node>) -> Self {
@@ -364,10 +901,92 @@ impl HtmlFragment {
         }
     }
 
-    fn to_html(&self) -> String {
-        match self {
-            HtmlFragment::Nodes(nodes) => nodes.iter().map(|node| node.to_html()).collect(),
+    /// Parses HTML, for example from a CMS or a markdown renderer, so it can be modified
+    /// like the output of the [html] macro. See the [parse] module for what is accepted.
+    ///
+    /// ```
+    /// # use hypersynthetic::HtmlFragment;
+    /// let mut page = HtmlFragment::parse(r#"<a href="/docs">Docs &amp; guides</a><br>"#).unwrap();
+    /// for link in page.iter_elements_mut().filter(|element| element.tag_name == "a") {
+    ///     link.set_attribute("rel".to_owned(), "nofollow".to_owned());
+    /// }
+    /// assert_eq!(
+    ///     page.to_string(),
+    ///     r#"<a href="/docs" rel="nofollow">Docs &amp; guides</a><br />"#
+    /// );
+    ///
+    /// let error = HtmlFragment::parse("<p>\n  <b>bold</p>").unwrap_err();
+    /// assert_eq!(error.to_string(), "expected `</b>`, found `</p>` at line 2, column 10");
+    /// ```
+    pub fn parse(html: &str) -> Result<Self, parse::ParseError> {
+        parse::parse(html)
+    }
+
+    /// Turns the markup the [html] macro renders at compile time back into the elements it is
+    /// rendered from. The rendered HTML stays the same, but the elements can be modified.
+    pub fn expand_markup(&mut self) {
+        let HtmlFragment::Nodes(nodes) = self;
+        let mut expanded = Vec::with_capacity(nodes.len());
+        for mut node in nodes.drain(..) {
+            let parsed = match &mut node {
+                Node::Static(markup) => Some(markup.nodes().clone()),
+                Node::Element(element) => {
+                    element.children.expand_markup();
+                    None
+                }
+                _ => None,
+            };
+            match parsed {
+                Some(HtmlFragment::Nodes(parsed)) => expanded.extend(parsed),
+                None => expanded.push(node),
+            }
         }
+        *nodes = expanded;
+    }
+
+    /// Renders the fragment into `w` in a single pass over the tree, without building
+    /// intermediate strings.
+    pub fn render_to<W: fmt::Write + ?Sized>(&self, w: &mut W) -> fmt::Result {
+        self.iter().try_for_each(|node| node.render_to(w))
+    }
+
+    /// Same as [HtmlFragment::render_to], but for byte sinks like files and sockets.
+    pub fn write_to<W: io::Write + ?Sized>(&self, w: &mut W) -> io::Result<()> {
+        write_via_fmt(w, |adapter| self.render_to(adapter))
+    }
+
+    /// Returns the exact length in bytes of the rendered HTML.
+    pub fn size_hint(&self) -> usize {
+        self.iter().map(Node::size_hint).sum()
+    }
+
+    /// Renders the fragment into a `String` allocated once with [HtmlFragment::size_hint] bytes.
+    /// Gives the same result as `to_string()`, which grows the `String` as it writes.
+    pub fn render_string(&self) -> String {
+        render_to_string(self.size_hint(), |s| self.render_to(s))
+    }
+
+    /// Renders the fragment with every block-level element on its own line and its children
+    /// indented, for reading and snapshot tests. See the [pretty] module for the layout.
+    ///
+    /// ```
+    /// # use hypersynthetic::{html, pretty::PrettyOptions};
+    /// let nav = html! {
+    ///     <nav><ul><li><a href="/">"Home"</a></li><li>"About "<b>"us"</b></li></ul></nav>
+    /// };
+    /// assert_eq!(
+    ///     nav.to_pretty_string(&PrettyOptions::default()),
+    ///     "\
+    /// <nav>
+    ///   <ul>
+    ///     <li><a href=\"/\">Home</a></li>
+    ///     <li>About <b>us</b></li>
+    ///   </ul>
+    /// </nav>"
+    /// );
+    /// ```
+    pub fn to_pretty_string(&self, options: &pretty::PrettyOptions) -> String {
+        pretty::to_pretty_string(self, options)
     }
 
     pub fn get_nodes(&self) -> Vec<Node> {
@@ -388,6 +1007,12 @@ impl HtmlFragment {
         }
     }
 
+    /// Iterates over the nodes with static markup replaced by the nodes it is rendered from,
+    /// which become siblings of the nodes around it.
+    pub(crate) fn iter_expanded(&self) -> ExpandedIter<'_> {
+        self.iter().flat_map(expand_static)
+    }
+
     pub fn iter_elements(&self) -> ElementDataIter<'_> {
         ElementDataIter { iter: self.iter() }
     }
@@ -397,6 +1022,119 @@ impl HtmlFragment {
             iter: self.iter_mut(),
         }
     }
+
+    /// Iterates over every node of the tree in document order, with its parent and depth.
+    ///
+    /// ```
+    /// # use hypersynthetic::HtmlFragment;
+    /// let page = HtmlFragment::parse("<ul><li>One</li></ul><p>Two</p>").unwrap();
+    /// let nodes: Vec<String> = page
+    ///     .iter_depth_first()
+    ///     .map(|item| format!("{}:{}", item.depth, item.node.to_string()))
+    ///     .collect();
+    /// assert_eq!(nodes, ["0:<ul><li>One</li></ul>", "1:<li>One</li>", "2:One", "0:<p>Two</p>", "1:Two"]);
+    /// ```
+    pub fn iter_depth_first(&self) -> visit::DepthFirst<'_> {
+        visit::DepthFirst::new(self)
+    }
+
+    /// Iterates over every node of the tree level by level, with its parent and depth.
+    pub fn iter_breadth_first(&self) -> visit::BreadthFirst<'_> {
+        visit::BreadthFirst::new(self)
+    }
+
+    /// Calls the [Visitor](visit::Visitor) for every node of the tree in document order.
+    pub fn visit(&self, visitor: &mut (impl visit::Visitor + ?Sized)) {
+        visit::visit(self, visitor);
+    }
+
+    /// Calls the [VisitorMut](visit::VisitorMut) for every node of the tree in document order.
+    /// Calls [HtmlFragment::expand_markup] first, so every element is visited.
+    pub fn visit_mut(&mut self, visitor: &mut (impl visit::VisitorMut + ?Sized)) {
+        self.expand_markup();
+        visit::visit_mut(self, visitor);
+    }
+
+    /// Returns the elements anywhere in the fragment that match a CSS selector,
+    /// in document order. See the [select] module for the supported selectors.
+    ///
+    /// ```
+    /// # use hypersynthetic::HtmlFragment;
+    /// let page = HtmlFragment::parse(
+    ///     r#"<nav><a href="/">Home</a><a href="https://example.com">Out</a></nav>"#,
+    /// )
+    /// .unwrap();
+    /// let external: Vec<_> = page
+    ///     .select("nav > a[href^=http]")
+    ///     .map(|a| a.get_attribute("href").unwrap())
+    ///     .collect();
+    /// assert_eq!(external, ["https://example.com"]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If the selector is invalid. Use [HtmlFragment::try_select] for selectors that are not
+    /// known in advance.
+    pub fn select(&self, selector: &str) -> select::Select<'_> {
+        self.try_select(selector)
+            .unwrap_or_else(|error| panic!("invalid selector `{selector}`: {error}"))
+    }
+
+    pub fn try_select(&self, selector: &str) -> Result<select::Select<'_>, select::SelectorError> {
+        Ok(select::select(self, &select::Selector::parse(selector)?))
+    }
+
+    /// The same as [HtmlFragment::select], but the tag names and attributes of the elements
+    /// can be modified. Calls [HtmlFragment::expand_markup] first, so every element is found.
+    ///
+    /// ```
+    /// # use hypersynthetic::html;
+    /// let mut page = html! {
+    ///     <main>
+    ///         <img src="a.png" />
+    ///         <p><img src="b.png" /></p>
+    ///     </main>
+    /// };
+    /// for mut img in page.select_mut("main img") {
+    ///     img.set_attribute("loading".to_owned(), "lazy".to_owned());
+    /// }
+    /// assert_eq!(
+    ///     page.to_string(),
+    ///     r#"<main><img src="a.png" loading="lazy" /><p><img src="b.png" loading="lazy" /></p></main>"#
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If the selector is invalid.
+    pub fn select_mut(&mut self, selector: &str) -> select::SelectMut<'_> {
+        self.try_select_mut(selector)
+            .unwrap_or_else(|error| panic!("invalid selector `{selector}`: {error}"))
+    }
+
+    pub fn try_select_mut(
+        &mut self,
+        selector: &str,
+    ) -> Result<select::SelectMut<'_>, select::SelectorError> {
+        Ok(select::select_mut(
+            self,
+            &select::Selector::parse(selector)?,
+        ))
+    }
+}
+
+impl Default for HtmlFragment {
+    fn default() -> Self {
+        HtmlFragment::new(Vec::new())
+    }
+}
+
+impl FromStr for HtmlFragment {
+    type Err = parse::ParseError;
+
+    fn from_str(html: &str) -> Result<Self, Self::Err> {
+        HtmlFragment::parse(html)
+    }
 }
 
 impl<'a> IntoIterator for &'a HtmlFragment {
@@ -417,14 +1155,44 @@ impl<'a> IntoIterator for &'a mut HtmlFragment {
     }
 }
 
+const DOCTYPE: &str = "<!DOCTYPE html>";
+
 impl Node {
-    fn to_html(&self) -> String {
+    pub fn render_to<W: fmt::Write + ?Sized>(&self, w: &mut W) -> fmt::Result {
+        match self {
+            Node::Text(text) => w.write_str(text),
+            Node::Element(element_data) => element_data.render_to(w),
+            Node::DocType => w.write_str(DOCTYPE),
+            Node::Comment(text) => {
+                w.write_str("<!--")?;
+                w.write_str(text)?;
+                w.write_str("-->")
+            }
+            Node::Static(markup) => w.write_str(markup.as_str()),
+            Node::Pending(_) => Ok(()),
+        }
+    }
+
+    pub fn write_to<W: io::Write + ?Sized>(&self, w: &mut W) -> io::Result<()> {
+        write_via_fmt(w, |adapter| self.render_to(adapter))
+    }
+
+    pub fn size_hint(&self) -> usize {
         match self {
-            Node::Text(text) => text.clone(),
-            Node::Element(element_data) => element_data.to_html(),
-            Node::DocType => "<!DOCTYPE html>".to_owned(),
+            Node::Text(text) => text.len(),
+            Node::Element(element_data) => element_data.size_hint(),
+            Node::DocType => DOCTYPE.len(),
+            // `<!--` + text + `-->`
+            Node::Comment(text) => 4 + text.len() + 3,
+            Node::Static(markup) => markup.as_str().len(),
+            Node::Pending(_) => 0,
         }
     }
+
+    /// Same as [HtmlFragment::render_string].
+    pub fn render_string(&self) -> String {
+        render_to_string(self.size_hint(), |s| self.render_to(s))
+    }
 }
 
 impl ElementData {
@@ -463,29 +1231,276 @@ impl ElementData {
             .map(|attr| attr.value.clone().unwrap_or("".to_owned()))
     }
 
-    fn to_html(&self) -> String {
-        let attributes_string: String = self
+    pub fn render_to<W: fmt::Write + ?Sized>(&self, w: &mut W) -> fmt::Result {
+        self.render_tag_with_attributes(w)?;
+
+        if self.self_closing {
+            return w.write_str(" />");
+        }
+
+        w.write_char('>')?;
+        self.children.render_to(w)?;
+        w.write_str("</")?;
+        w.write_str(&self.tag_name)?;
+        w.write_char('>')
+    }
+
+    /// Writes `<tag` and the attributes, without the closing `>`.
+    pub(crate) fn render_tag_with_attributes<W: fmt::Write + ?Sized>(
+        &self,
+        w: &mut W,
+    ) -> fmt::Result {
+        w.write_char('<')?;
+        w.write_str(&self.tag_name)?;
+        for attr in &self.attributes {
+            w.write_char(' ')?;
+            w.write_str(&attr.name)?;
+            if let Some(value) = &attr.value {
+                w.write_str("=\"")?;
+                w.write_str(value)?;
+                w.write_char('"')?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn write_to<W: io::Write + ?Sized>(&self, w: &mut W) -> io::Result<()> {
+        write_via_fmt(w, |adapter| self.render_to(adapter))
+    }
+
+    pub fn size_hint(&self) -> usize {
+        let attributes: usize = self
             .attributes
             .iter()
             .map(|attr| match &attr.value {
-                Some(value) => format!(" {}=\"{}\"", attr.name, value),
-                None => format!(" {}", attr.name),
+                // ` name="value"`
+                Some(value) => attr.name.len() + value.len() + 4,
+                // ` name`
+                None => attr.name.len() + 1,
             })
-            .collect();
-
-        let children_string: String = match &self.children {
-            HtmlFragment::Nodes(nodes) => nodes.iter().map(|node| node.to_html()).collect(),
-        };
+            .sum();
 
         if self.self_closing {
-            format!("<{}{} />", self.tag_name, attributes_string)
+            // `<tag` + attributes + ` />`
+            1 + self.tag_name.len() + attributes + 3
         } else {
-            format!(
-                "<{}{}>{}</{}>",
-                self.tag_name, attributes_string, children_string, self.tag_name
-            )
+            // `<tag` + attributes + `>` + children + `</tag>`
+            1 + self.tag_name.len()
+                + attributes
+                + 1
+                + self.children.size_hint()
+                + 3
+                + self.tag_name.len()
         }
     }
+
+    /// Same as [HtmlFragment::render_string].
+    pub fn render_string(&self) -> String {
+        render_to_string(self.size_hint(), |s| self.render_to(s))
+    }
+}
+
+impl StaticMarkup {
+    /// `nodes` builds the nodes that render to `html`.
+    pub const fn new(html: &'static str, nodes: fn() -> HtmlFragment) -> Self {
+        StaticMarkup {
+            html,
+            nodes: LazyLock::new(nodes),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        self.html
+    }
+
+    pub fn nodes(&self) -> &HtmlFragment {
+        &self.nodes
+    }
+}
+
+pub(crate) type ExpandedIter<'a> =
+    FlatMap<Iter<'a, Node>, Iter<'a, Node>, fn(&Node) -> Iter<'_, Node>>;
+
+fn expand_static(node: &Node) -> Iter<'_, Node> {
+    match node {
+        Node::Static(markup) => markup.nodes().iter(),
+        node => std::slice::from_ref(node).iter(),
+    }
+}
+
+/// Where an iteration of `:for={pat in collection, info}` is in the collection.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LoopInfo {
+    /// Starts at 0.
+    pub index: usize,
+    pub first: bool,
+    pub last: bool,
+    /// The index is even; the first iteration is even.
+    pub even: bool,
+    pub odd: bool,
+}
+
+impl LoopInfo {
+    pub fn new(index: usize, last: bool) -> Self {
+        LoopInfo {
+            index,
+            first: index == 0,
+            last,
+            even: index.is_multiple_of(2),
+            odd: !index.is_multiple_of(2),
+        }
+    }
+}
+
+/// A set of attributes built at runtime, to be spread onto an element with `{..attributes}`
+/// in the [html] macro.
+///
+/// Names and values are stored unescaped; they are escaped when spread, values for their
+/// [AttributeContext](escape::AttributeContext) as well unless they are set with
+/// [set_trusted](Attributes::set_trusted). Each name appears once, and setting it again
+/// replaces the value in place.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Attributes {
+    attributes: Vec<Attribute>,
+    /// Names of the attributes whose values are only escaped for HTML
+    trusted: Vec<String>,
+}
+
+impl Attributes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(&mut self, name: impl Into<String>, value: impl ToString) {
+        self.insert(name.into(), Some(value.to_string()));
+    }
+
+    /// Sets an attribute whose value is only escaped for HTML when spread, like a
+    /// [Trusted](escape::Trusted) value in the [html] macro.
+    pub fn set_trusted(&mut self, name: impl Into<String>, value: impl ToString) {
+        let name = name.into();
+        self.insert(name.clone(), Some(value.to_string()));
+        self.trusted.push(name);
+    }
+
+    /// Sets an attribute without a value, like `disabled` or `hidden`.
+    pub fn set_flag(&mut self, name: impl Into<String>) {
+        self.insert(name.into(), None);
+    }
+
+    /// Returns the value of an attribute. Attributes without a value give an empty string.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|attr| attr.name == name)
+            .map(|attr| attr.value.as_deref().unwrap_or(""))
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.attributes.iter().any(|attr| attr.name == name)
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Attribute> {
+        let index = self.attributes.iter().position(|attr| attr.name == name)?;
+        self.trusted.retain(|trusted| trusted != name);
+        Some(self.attributes.remove(index))
+    }
+
+    pub(crate) fn is_trusted(&self, name: &str) -> bool {
+        self.trusted.iter().any(|trusted| trusted == name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.attributes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.attributes.is_empty()
+    }
+
+    pub fn iter(&self) -> Iter<'_, Attribute> {
+        self.attributes.iter()
+    }
+
+    fn insert(&mut self, name: String, value: Option<String>) {
+        self.trusted.retain(|trusted| *trusted != name);
+        match self.attributes.iter_mut().find(|attr| attr.name == name) {
+            Some(attr) => attr.value = value,
+            None => self.attributes.push(Attribute { name, value }),
+        }
+    }
+}
+
+impl<K: Into<String>, V: ToString> FromIterator<(K, V)> for Attributes {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut attributes = Attributes::new();
+        attributes.extend(iter);
+        attributes
+    }
+}
+
+impl<K: Into<String>, V: ToString> Extend<(K, V)> for Attributes {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (name, value) in iter {
+            self.set(name, value);
+        }
+    }
+}
+
+impl IntoIterator for Attributes {
+    type Item = Attribute;
+    type IntoIter = std::vec::IntoIter<Attribute>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.attributes.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Attributes {
+    type Item = &'a Attribute;
+    type IntoIter = Iter<'a, Attribute>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.attributes.iter()
+    }
+}
+
+fn render_to_string(capacity: usize, render: impl FnOnce(&mut String) -> fmt::Result) -> String {
+    let mut s = String::with_capacity(capacity);
+    render(&mut s).expect("writing to a String never fails");
+    s
+}
+
+/// Bridges [fmt::Write] to [io::Write], keeping the underlying io error
+/// because [fmt::Error] carries no information.
+struct IoAdapter<'a, W: io::Write + ?Sized> {
+    inner: &'a mut W,
+    error: Option<io::Error>,
+}
+
+impl<W: io::Write + ?Sized> fmt::Write for IoAdapter<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|e| {
+            self.error = Some(e);
+            fmt::Error
+        })
+    }
+}
+
+fn write_via_fmt<W: io::Write + ?Sized>(
+    w: &mut W,
+    render: impl FnOnce(&mut IoAdapter<'_, W>) -> fmt::Result,
+) -> io::Result<()> {
+    let mut adapter = IoAdapter {
+        inner: w,
+        error: None,
+    };
+    match render(&mut adapter) {
+        Ok(()) => Ok(()),
+        Err(_) => Err(adapter
+            .error
+            .unwrap_or_else(|| io::Error::other("formatter error"))),
+    }
 }
 
 pub struct ElementDataIter<'a> {
@@ -524,19 +1539,19 @@ impl<'a> Iterator for ElementDataIterMut<'a> {
 
 impl fmt::Display for ElementData {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.to_html())
+        self.render_to(f)
     }
 }
 
 impl fmt::Display for Node {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.to_html())
+        self.render_to(f)
     }
 }
 
 impl fmt::Display for HtmlFragment {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.to_html())
+        self.render_to(f)
     }
 }
 
@@ -546,7 +1561,7 @@ impl<'r> rocket::response::Responder<'r, 'static> for HtmlFragment {
         self,
         req: &'r rocket::request::Request<'_>,
     ) -> rocket::response::Result<'static> {
-        let content = self.to_string();
+        let content = self.render_string();
         rocket::response::Response::build_from(content.respond_to(req)?)
             .header(rocket::http::ContentType::HTML)
             .ok()
@@ -556,7 +1571,7 @@ impl<'r> rocket::response::Responder<'r, 'static> for HtmlFragment {
 #[cfg(feature = "axum")]
 impl axum::response::IntoResponse for HtmlFragment {
     fn into_response(self) -> axum::response::Response {
-        axum::response::Html(self.to_string()).into_response()
+        axum::response::Html(self.render_string()).into_response()
     }
 }
 
@@ -582,7 +1597,7 @@ mod tests {
         let document = Node::Element(body);
 
         assert_eq!(
-            document.to_html(),
+            document.to_string(),
             "<body>Hello, Rust!<div class=\"container\">This is inside a div.</div></body>"
         )
     }
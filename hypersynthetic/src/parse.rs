@@ -0,0 +1,478 @@
+//! Parsing HTML at runtime, see [HtmlFragment::parse].
+//!
+//! The parser follows the tokenizer rules of HTML, but builds the tree strictly: every element
+//! that is not void has to be closed in order, and a mismatch is a [ParseError] rather than a
+//! repaired tree. The end tags HTML allows to leave out are implied, though: `<li>`, `<dt>`,
+//! `<dd>`, `<option>`, `<tr>`, `<td>` and `<th>` are closed by a sibling that starts, `<p>` by
+//! a block like `<div>` or `<ul>` as well, and all of them by the end tag of their parent or
+//! the end of the input.
+//!
+//! The nodes are stored the same way the [html](crate::html) macro stores them. Text and
+//! attribute values stay escaped for HTML, with character references kept as written, and the
+//! content of `<script>` and `<style>` is kept as is. Void elements are self-closing, and so are
+//! elements closed with `/>` inside `<svg>` and `<math>`. Elsewhere `/>` is ignored like in HTML,
+//! so `<div/>` has to be closed with `</div>`. Tag and attribute names are lowercased. Comments
+//! are kept, escaped with [escape_comment](crate::escape::escape_comment), and other markup that
+//! HTML parses as a comment, like `<?xml ...?>`, is dropped.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::escape::{
+    escape_attribute_keeping_references, escape_comment, escape_text_keeping_references,
+};
+use crate::{Attribute, ElementData, HtmlFragment, Node};
+
+/// Elements that can't have children or an end tag.
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+/// Elements whose content is taken as is up to their end tag.
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style"];
+
+/// Elements whose content is text with character references up to their end tag.
+const ESCAPABLE_RAW_TEXT_ELEMENTS: &[&str] = &["textarea", "title"];
+
+/// Elements whose end tag can be left out, see [is_ended_by].
+const OPTIONAL_END_TAG_ELEMENTS: &[&str] = &["dd", "dt", "li", "option", "p", "td", "th", "tr"];
+
+/// Elements whose start tag ends an open `<p>`.
+const ENDS_PARAGRAPH: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "dd",
+    "details",
+    "dialog",
+    "div",
+    "dl",
+    "dt",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hgroup",
+    "hr",
+    "li",
+    "main",
+    "menu",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "ul",
+];
+
+/// Why and where [HtmlFragment::parse] failed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub message: String,
+    /// Starts at 1.
+    pub line: usize,
+    /// Starts at 1 and counts characters, not bytes.
+    pub column: usize,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at line {}, column {}",
+            self.message, self.line, self.column
+        )
+    }
+}
+
+impl Error for ParseError {}
+
+pub(crate) fn parse(html: &str) -> Result<HtmlFragment, ParseError> {
+    Parser::new(html).parse()
+}
+
+struct Parser<'a> {
+    html: &'a str,
+    /// Byte offset of the next character.
+    pos: usize,
+    /// Elements that are not closed yet, with the offset of their start tag.
+    open: Vec<(ElementData, usize)>,
+    root: HtmlFragment,
+}
+
+struct StartTag {
+    name: String,
+    attributes: Vec<Attribute>,
+    self_closing: bool,
+}
+
+impl<'a> Parser<'a> {
+    fn new(html: &'a str) -> Self {
+        Parser {
+            html,
+            pos: 0,
+            open: Vec::new(),
+            root: HtmlFragment::default(),
+        }
+    }
+
+    fn parse(mut self) -> Result<HtmlFragment, ParseError> {
+        while !self.rest().is_empty() {
+            let rest = self.rest();
+            if rest.starts_with("<!--") {
+                self.parse_comment()?;
+            } else if starts_with_ignore_case(rest, "<!doctype") {
+                self.parse_doctype()?;
+            } else if rest.starts_with("<!") || rest.starts_with("<?") {
+                self.skip_bogus_comment();
+            } else if rest.starts_with("</") && starts_with_tag_name(&rest[2..]) {
+                self.parse_end_tag()?;
+            } else if rest.starts_with('<') && starts_with_tag_name(&rest[1..]) {
+                self.parse_element()?;
+            } else {
+                // A `<` that doesn't start markup is text
+                let first = rest.chars().next().map_or(0, char::len_utf8);
+                let end = rest[first..].find('<').map_or(rest.len(), |i| i + first);
+                let text = escape_text_keeping_references(&rest[..end]);
+                self.pos += end;
+                self.push_text(text);
+            }
+        }
+
+        self.close_implied(|_| true);
+        match self.open.pop() {
+            Some((element, start)) => {
+                Err(self.error_at(start, format!("unclosed `<{}>`", element.tag_name)))
+            }
+            None => Ok(self.root),
+        }
+    }
+
+    /// Closes the current element while its end tag can be left out and `ends` says it ends.
+    fn close_implied(&mut self, ends: impl Fn(&str) -> bool) {
+        while let Some((element, _)) = self.open.last() {
+            let name = element.tag_name.to_ascii_lowercase();
+            if !OPTIONAL_END_TAG_ELEMENTS.contains(&name.as_str()) || !ends(&name) {
+                break;
+            }
+            let (element, _) = self.open.pop().expect("an element is open");
+            self.push(Node::Element(element));
+        }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.html[self.pos..]
+    }
+
+    fn push(&mut self, node: Node) {
+        match self.open.last_mut() {
+            Some((element, _)) => element.add_child(node),
+            None => self.root.push(node),
+        }
+    }
+
+    /// Pushes text, merging it with a preceding text node.
+    fn push_text(&mut self, text: String) {
+        let nodes = match self.open.last_mut() {
+            Some((element, _)) => &mut element.children,
+            None => &mut self.root,
+        };
+        match nodes.iter_mut().last() {
+            Some(Node::Text(previous)) => previous.push_str(&text),
+            _ => nodes.push(Node::Text(text)),
+        }
+    }
+
+    fn parse_comment(&mut self) -> Result<(), ParseError> {
+        let start = self.pos;
+        self.pos += "<!--".len();
+        // `<!-->` and `<!--->` are empty comments
+        for empty in [">", "->"] {
+            if self.rest().starts_with(empty) {
+                self.pos += empty.len();
+                self.push(Node::Comment(String::new()));
+                return Ok(());
+            }
+        }
+        match self.rest().find("-->") {
+            Some(end) => {
+                let text = escape_comment(&self.rest()[..end]);
+                self.pos += end + "-->".len();
+                self.push(Node::Comment(text));
+                Ok(())
+            }
+            None => Err(self.error_at(start, "unclosed comment")),
+        }
+    }
+
+    /// `<!...>` and `<?...>` that are not comments or a doctype are ignored up to the next `>`.
+    fn skip_bogus_comment(&mut self) {
+        self.pos += self
+            .rest()
+            .find('>')
+            .map_or(self.rest().len(), |end| end + 1);
+    }
+
+    fn parse_doctype(&mut self) -> Result<(), ParseError> {
+        let start = self.pos;
+        self.pos += "<!doctype".len();
+        let Some(end) = self.rest().find('>') else {
+            return Err(self.error_at(start, "unclosed doctype"));
+        };
+        let name = self.rest()[..end].split_whitespace().next().unwrap_or("");
+        if !name.eq_ignore_ascii_case("html") {
+            return Err(self.error_at(start, "only `<!DOCTYPE html>` is supported"));
+        }
+        self.pos += end + 1;
+        self.push(Node::DocType);
+        Ok(())
+    }
+
+    fn parse_element(&mut self) -> Result<(), ParseError> {
+        let start = self.pos;
+        let tag = self.parse_start_tag()?;
+        let lowercase_name = tag.name.to_ascii_lowercase();
+        let is_void = VOID_ELEMENTS.contains(&lowercase_name.as_str());
+        self.close_implied(|open| is_ended_by(open, &lowercase_name));
+        // Like in HTML, `/>` is ignored on other elements, which stay open
+        let is_foreign =
+            matches!(lowercase_name.as_str(), "svg" | "math") || self.in_foreign_content();
+        let mut element = ElementData::new(tag.name);
+        element.attributes = tag.attributes;
+
+        if is_void || (tag.self_closing && is_foreign) {
+            element.self_closing = true;
+            self.push(Node::Element(element));
+            return Ok(());
+        }
+
+        let is_raw = RAW_TEXT_ELEMENTS.contains(&lowercase_name.as_str());
+        if is_raw || ESCAPABLE_RAW_TEXT_ELEMENTS.contains(&lowercase_name.as_str()) {
+            let Some(end) = find_end_tag(self.rest(), &element.tag_name) else {
+                return Err(self.error_at(start, format!("unclosed `<{}>`", element.tag_name)));
+            };
+            let content = &self.rest()[..end];
+            if !content.is_empty() {
+                let text = if is_raw {
+                    content.to_owned()
+                } else {
+                    escape_text_keeping_references(content)
+                };
+                element.add_child(Node::Text(text));
+            }
+            self.pos += end;
+            self.skip_end_tag();
+            self.push(Node::Element(element));
+            return Ok(());
+        }
+
+        self.open.push((element, start));
+        Ok(())
+    }
+
+    /// Whether the open elements are in an `<svg>` or `<math>` element, outside of the HTML in
+    /// a `<foreignObject>`.
+    fn in_foreign_content(&self) -> bool {
+        self.open
+            .iter()
+            .rev()
+            .find_map(|(element, _)| match element.tag_name.as_str() {
+                "svg" | "math" => Some(true),
+                "foreignobject" => Some(false),
+                _ => None,
+            })
+            .unwrap_or(false)
+    }
+
+    fn parse_start_tag(&mut self) -> Result<StartTag, ParseError> {
+        let start = self.pos;
+        self.pos += 1;
+        let name = self.take_while(|c| !c.is_ascii_whitespace() && c != '/' && c != '>');
+        let mut tag = StartTag {
+            name: name.to_ascii_lowercase(),
+            attributes: Vec::new(),
+            self_closing: false,
+        };
+
+        loop {
+            self.take_while(|c| c.is_ascii_whitespace());
+            let rest = self.rest();
+            if rest.is_empty() {
+                return Err(self.error_at(start, format!("unclosed `<{}` tag", tag.name)));
+            } else if rest.starts_with('>') {
+                self.pos += 1;
+                return Ok(tag);
+            } else if rest.starts_with("/>") {
+                self.pos += 2;
+                tag.self_closing = true;
+                return Ok(tag);
+            } else if rest.starts_with('/') {
+                self.pos += 1;
+                continue;
+            }
+
+            let attr_start = self.pos;
+            // The first character may be `=`, which is then a part of the name
+            self.pos += rest.chars().next().map_or(0, char::len_utf8);
+            self.take_while(|c| !c.is_ascii_whitespace() && !matches!(c, '/' | '>' | '='));
+            let name = self.html[attr_start..self.pos].to_ascii_lowercase();
+            if matches!(name.chars().next(), Some('"' | '\'' | '<')) {
+                return Err(self.error_at(attr_start, "unexpected character in attribute name"));
+            }
+
+            self.take_while(|c| c.is_ascii_whitespace());
+            let value = if self.rest().starts_with('=') {
+                self.pos += 1;
+                self.take_while(|c| c.is_ascii_whitespace());
+                Some(self.parse_attribute_value(start, &tag.name)?)
+            } else {
+                None
+            };
+
+            // Later duplicates are ignored
+            if !tag
+                .attributes
+                .iter()
+                .any(|attr| attr.name.eq_ignore_ascii_case(&name))
+            {
+                tag.attributes.push(Attribute { name, value });
+            }
+        }
+    }
+
+    fn parse_attribute_value(
+        &mut self,
+        tag_start: usize,
+        tag_name: &str,
+    ) -> Result<String, ParseError> {
+        let value = match self.rest().chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                self.pos += 1;
+                let Some(end) = self.rest().find(quote) else {
+                    return Err(self.error_at(tag_start, format!("unclosed `<{tag_name}` tag")));
+                };
+                let value = &self.rest()[..end];
+                self.pos += end + 1;
+                value
+            }
+            _ => self.take_while(|c| !c.is_ascii_whitespace() && c != '>'),
+        };
+        Ok(escape_attribute_keeping_references(value))
+    }
+
+    fn parse_end_tag(&mut self) -> Result<(), ParseError> {
+        let start = self.pos;
+        self.pos += 2;
+        let name = self.take_while(|c| !c.is_ascii_whitespace() && c != '/' && c != '>');
+        let name = name.to_ascii_lowercase();
+        // End tags can't have attributes, but the tokenizer skips them
+        let Some(end) = self.rest().find('>') else {
+            return Err(self.error_at(start, format!("unclosed `</{name}` tag")));
+        };
+        self.pos += end + 1;
+
+        if VOID_ELEMENTS.contains(&name.to_ascii_lowercase().as_str()) {
+            return Err(self.error_at(
+                start,
+                format!("`<{name}>` is a void element and can't have an end tag"),
+            ));
+        }
+        // The end tag of a parent ends the children whose end tags were left out
+        let is_open = |open: &[(ElementData, usize)]| {
+            open.iter()
+                .any(|(element, _)| element.tag_name.eq_ignore_ascii_case(&name))
+        };
+        if is_open(&self.open) {
+            self.close_implied(|open| !open.eq_ignore_ascii_case(&name));
+        }
+        match self.open.pop() {
+            Some((element, _)) if element.tag_name.eq_ignore_ascii_case(&name) => {
+                self.push(Node::Element(element));
+                Ok(())
+            }
+            Some((element, _)) => Err(self.error_at(
+                start,
+                format!("expected `</{}>`, found `</{name}>`", element.tag_name),
+            )),
+            None => Err(self.error_at(start, format!("unexpected `</{name}>`"))),
+        }
+    }
+
+    /// Skips the end tag of a raw text element, which [find_end_tag] has found.
+    fn skip_end_tag(&mut self) {
+        self.pos += self
+            .rest()
+            .find('>')
+            .map_or(self.rest().len(), |end| end + 1);
+    }
+
+    fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> &'a str {
+        let start = self.pos;
+        let len = self
+            .rest()
+            .find(|c| !predicate(c))
+            .unwrap_or(self.rest().len());
+        self.pos += len;
+        &self.html[start..self.pos]
+    }
+
+    fn error_at(&self, offset: usize, message: impl Into<String>) -> ParseError {
+        let before = &self.html[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        ParseError {
+            message: message.into(),
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+}
+
+/// Whether a start tag of `next` ends an open `element` whose end tag was left out.
+/// Both names are lowercase.
+fn is_ended_by(element: &str, next: &str) -> bool {
+    match element {
+        "li" => next == "li",
+        "dt" | "dd" => matches!(next, "dt" | "dd"),
+        "option" => matches!(next, "option" | "optgroup"),
+        "td" | "th" => matches!(next, "td" | "th" | "tr"),
+        "tr" => next == "tr",
+        "p" => ENDS_PARAGRAPH.contains(&next),
+        _ => false,
+    }
+}
+
+fn starts_with_ignore_case(s: &str, prefix: &str) -> bool {
+    s.len() >= prefix.len() && s.as_bytes()[..prefix.len()].eq_ignore_ascii_case(prefix.as_bytes())
+}
+
+fn starts_with_tag_name(s: &str) -> bool {
+    s.starts_with(|c: char| c.is_ascii_alphabetic())
+}
+
+/// Finds `</name` followed by whitespace, `/` or `>`, ignoring case.
+fn find_end_tag(s: &str, name: &str) -> Option<usize> {
+    let mut from = 0;
+    while let Some(i) = s[from..].find("</") {
+        let start = from + i;
+        let after = &s[start + 2..];
+        if starts_with_ignore_case(after, name)
+            && after[name.len()..]
+                .starts_with(|c: char| c.is_ascii_whitespace() || c == '/' || c == '>')
+        {
+            return Some(start);
+        }
+        from = start + 2;
+    }
+    None
+}
@@ -0,0 +1,271 @@
+//! Indented HTML for reading, debugging and snapshot tests.
+//!
+//! [HtmlFragment::to_pretty_string] puts every block-level element on its own line and
+//! indents its children. Everything else — text, comments, inline elements like `<a>` or `<b>`,
+//! and elements that aren't known to be block-level — stays on the line of the text around it,
+//! because whitespace between inline content is visible on the page. Long lines of inline
+//! content are only broken where they already contain whitespace.
+//!
+//! Whitespace in text is collapsed to a single space, except inside `<pre>`, `<textarea>`,
+//! `<script>` and `<style>`, which are written out unchanged. The page looks the same as with
+//! the minified output, as long as the CSS doesn't change `white-space` to keep it.
+
+use crate::{ElementData, HtmlFragment, Node};
+
+/// How [HtmlFragment::to_pretty_string] lays out the HTML.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PrettyOptions {
+    /// Added in front of a line for every level of nesting. Two spaces by default.
+    pub indent: String,
+    /// The width in characters, including the indentation, that lines are kept within when
+    /// possible. 80 by default.
+    pub line_width: usize,
+}
+
+impl Default for PrettyOptions {
+    fn default() -> Self {
+        PrettyOptions {
+            indent: "  ".to_owned(),
+            line_width: 80,
+        }
+    }
+}
+
+const BLOCK_ELEMENTS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "base",
+    "blockquote",
+    "body",
+    "caption",
+    "col",
+    "colgroup",
+    "dd",
+    "details",
+    "dialog",
+    "div",
+    "dl",
+    "dt",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "head",
+    "header",
+    "hgroup",
+    "hr",
+    "html",
+    "legend",
+    "li",
+    "link",
+    "main",
+    "menu",
+    "meta",
+    "nav",
+    "noscript",
+    "ol",
+    "optgroup",
+    "option",
+    "p",
+    "pre",
+    "script",
+    "section",
+    "style",
+    "summary",
+    "table",
+    "tbody",
+    "td",
+    "template",
+    "tfoot",
+    "th",
+    "thead",
+    "title",
+    "tr",
+    "ul",
+];
+
+/// Elements whose content is written out as it is.
+const PRESERVED_ELEMENTS: &[&str] = &["pre", "script", "style", "textarea"];
+
+fn is_one_of(element: &ElementData, names: &[&str]) -> bool {
+    names
+        .iter()
+        .any(|name| element.tag_name.eq_ignore_ascii_case(name))
+}
+
+fn is_block(node: &Node) -> bool {
+    match node {
+        Node::Element(element) => is_one_of(element, BLOCK_ELEMENTS),
+        Node::DocType => true,
+        Node::Text(_) | Node::Comment(_) | Node::Static(_) | Node::Pending(_) => false,
+    }
+}
+
+fn is_html_whitespace(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\n' | '\r' | '\x0C')
+}
+
+/// Markup rendered at compile time is laid out like the nodes it is rendered from.
+pub(crate) fn to_pretty_string(fragment: &HtmlFragment, options: &PrettyOptions) -> String {
+    let mut printer = Printer {
+        options,
+        lines: Vec::new(),
+    };
+    printer.nodes(fragment, 0);
+    printer.lines.join("\n")
+}
+
+struct Printer<'a> {
+    options: &'a PrettyOptions,
+    lines: Vec<String>,
+}
+
+impl Printer<'_> {
+    fn indent(&self, depth: usize) -> String {
+        self.options.indent.repeat(depth)
+    }
+
+    /// Block-level nodes get lines of their own; the inline nodes between them are
+    /// written together.
+    fn nodes(&mut self, fragment: &HtmlFragment, depth: usize) {
+        let mut inline = Vec::new();
+        for node in fragment.iter_expanded() {
+            if is_block(node) {
+                self.inline(&inline, depth);
+                inline.clear();
+                self.block(node, depth);
+            } else {
+                inline.push(node);
+            }
+        }
+        self.inline(&inline, depth);
+    }
+
+    fn block(&mut self, node: &Node, depth: usize) {
+        let indent = self.indent(depth);
+        let Node::Element(element) = node else {
+            self.lines.push(format!("{indent}{node}"));
+            return;
+        };
+        if element.self_closing
+            || element.children.iter_expanded().next().is_none()
+            || is_one_of(element, PRESERVED_ELEMENTS)
+        {
+            self.lines.push(format!("{indent}{element}"));
+            return;
+        }
+
+        let mut start_tag = String::new();
+        element
+            .render_tag_with_attributes(&mut start_tag)
+            .expect("writing to a String never fails");
+        start_tag.push('>');
+        let end_tag = format!("</{}>", element.tag_name);
+
+        if !element.children.iter_expanded().any(is_block) {
+            let mut content = String::new();
+            for child in element.children.iter_expanded() {
+                collapsed(child, &mut content);
+            }
+            let line = format!(
+                "{indent}{start_tag}{}{end_tag}",
+                content.trim_matches(is_html_whitespace)
+            );
+            if line.chars().count() <= self.options.line_width {
+                self.lines.push(line);
+                return;
+            }
+        }
+
+        self.lines.push(format!("{indent}{start_tag}"));
+        self.nodes(&element.children, depth + 1);
+        self.lines.push(format!("{indent}{end_tag}"));
+    }
+
+    /// Writes inline content as words, breaking lines only where the content has whitespace.
+    fn inline(&mut self, nodes: &[&Node], depth: usize) {
+        let mut words = Vec::new();
+        let mut word = String::new();
+        for node in nodes {
+            match node {
+                Node::Text(text) => {
+                    for (i, part) in text.split(is_html_whitespace).enumerate() {
+                        if i > 0 && !word.is_empty() {
+                            words.push(std::mem::take(&mut word));
+                        }
+                        word.push_str(part);
+                    }
+                }
+                _ => collapsed(node, &mut word),
+            }
+        }
+        if !word.is_empty() {
+            words.push(word);
+        }
+
+        let indent = self.indent(depth);
+        let mut line = indent.clone();
+        let mut width = indent.chars().count();
+        for word in words {
+            let word_width = word.chars().count();
+            if line.len() > indent.len() {
+                if width + 1 + word_width > self.options.line_width {
+                    self.lines
+                        .push(std::mem::replace(&mut line, indent.clone()));
+                    width = indent.chars().count();
+                } else {
+                    line.push(' ');
+                    width += 1;
+                }
+            }
+            line.push_str(&word);
+            width += word_width;
+        }
+        if line.len() > indent.len() {
+            self.lines.push(line);
+        }
+    }
+}
+
+/// Renders a node on a single line, with whitespace in text collapsed to a single space.
+fn collapsed(node: &Node, out: &mut String) {
+    match node {
+        Node::Text(text) => {
+            let mut in_whitespace = false;
+            for c in text.chars() {
+                if is_html_whitespace(c) {
+                    if !in_whitespace {
+                        out.push(' ');
+                    }
+                    in_whitespace = true;
+                } else {
+                    out.push(c);
+                    in_whitespace = false;
+                }
+            }
+        }
+        Node::Element(element)
+            if !element.self_closing && !is_one_of(element, PRESERVED_ELEMENTS) =>
+        {
+            element
+                .render_tag_with_attributes(out)
+                .expect("writing to a String never fails");
+            out.push('>');
+            for child in element.children.iter_expanded() {
+                collapsed(child, out);
+            }
+            out.push_str("</");
+            out.push_str(&element.tag_name);
+            out.push('>');
+        }
+        _ => out.push_str(&node.to_string()),
+    }
+}
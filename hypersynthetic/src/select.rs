@@ -0,0 +1,713 @@
+//! CSS selectors for finding elements anywhere in an [HtmlFragment],
+//! see [HtmlFragment::select].
+//!
+//! Supported are type selectors and `*`, `#id`, `.class`, attribute selectors with the
+//! `=`, `~=`, `|=`, `^=`, `$=` and `*=` operators and the `i` flag, the descendant, `>`, `+`
+//! and `~` combinators, selector lists, and the `:first-child`, `:last-child`, `:only-child`,
+//! `:nth-child()`, `:nth-last-child()` and `:not()` pseudo-classes. Tag and attribute names
+//! are matched ignoring case.
+//!
+//! Only [Node::Element]s are searched, including the ones the [html](crate::html) macro
+//! renders at compile time. [HtmlFragment::select_mut] calls [HtmlFragment::expand_markup]
+//! first to modify them.
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::escape::unescape_attribute;
+use crate::{Attribute, ElementData, HtmlFragment, Node};
+
+/// A parsed selector list.
+#[derive(Clone, Debug)]
+pub struct Selector {
+    alternatives: Vec<ComplexSelector>,
+}
+
+/// Compound selectors joined with combinators: `combinators[i]` is between
+/// `compounds[i]` and `compounds[i + 1]`.
+#[derive(Clone, Debug)]
+struct ComplexSelector {
+    compounds: Vec<CompoundSelector>,
+    combinators: Vec<Combinator>,
+}
+
+#[derive(Clone, Debug)]
+struct CompoundSelector {
+    /// `None` for `*` or when the type is omitted.
+    tag_name: Option<String>,
+    conditions: Vec<Condition>,
+}
+
+#[derive(Clone, Debug)]
+enum Condition {
+    Id(String),
+    Class(String),
+    Attribute {
+        name: String,
+        matcher: Option<AttributeMatcher>,
+    },
+    /// `:nth-child(an+b)`, or `:nth-last-child(an+b)` when `from_end` is set.
+    NthChild {
+        a: i64,
+        b: i64,
+        from_end: bool,
+    },
+    Not(Vec<ComplexSelector>),
+}
+
+#[derive(Clone, Debug)]
+struct AttributeMatcher {
+    operator: AttributeOperator,
+    value: String,
+    ignore_case: bool,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum AttributeOperator {
+    /// `=`
+    Equals,
+    /// `~=`
+    Includes,
+    /// `|=`
+    DashMatch,
+    /// `^=`
+    Prefix,
+    /// `$=`
+    Suffix,
+    /// `*=`
+    Substring,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Combinator {
+    Descendant,
+    /// `>`
+    Child,
+    /// `+`
+    NextSibling,
+    /// `~`
+    SubsequentSibling,
+}
+
+/// Why and where [Selector::parse] failed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SelectorError {
+    pub message: String,
+    /// Starts at 1 and counts characters.
+    pub column: usize,
+}
+
+impl fmt::Display for SelectorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at column {}", self.message, self.column)
+    }
+}
+
+impl Error for SelectorError {}
+
+impl Selector {
+    pub fn parse(selector: &str) -> Result<Self, SelectorError> {
+        let mut parser = SelectorParser { selector, pos: 0 };
+        let alternatives = parser.parse_list()?;
+        match parser.peek() {
+            None => Ok(Selector { alternatives }),
+            Some(c) => Err(parser.error(format!("unexpected `{c}`"))),
+        }
+    }
+}
+
+impl FromStr for Selector {
+    type Err = SelectorError;
+
+    fn from_str(selector: &str) -> Result<Self, Self::Err> {
+        Selector::parse(selector)
+    }
+}
+
+struct SelectorParser<'a> {
+    selector: &'a str,
+    /// Byte offset of the next character.
+    pos: usize,
+}
+
+impl SelectorParser<'_> {
+    fn parse_list(&mut self) -> Result<Vec<ComplexSelector>, SelectorError> {
+        let mut list = Vec::new();
+        loop {
+            self.skip_whitespace();
+            list.push(self.parse_complex()?);
+            self.skip_whitespace();
+            if !self.eat(',') {
+                return Ok(list);
+            }
+        }
+    }
+
+    fn parse_complex(&mut self) -> Result<ComplexSelector, SelectorError> {
+        let mut complex = ComplexSelector {
+            compounds: vec![self.parse_compound()?],
+            combinators: Vec::new(),
+        };
+        loop {
+            let had_whitespace = self.skip_whitespace();
+            let combinator = if self.eat('>') {
+                Combinator::Child
+            } else if self.eat('+') {
+                Combinator::NextSibling
+            } else if self.eat('~') {
+                Combinator::SubsequentSibling
+            } else if had_whitespace && !matches!(self.peek(), None | Some(',' | ')')) {
+                Combinator::Descendant
+            } else {
+                return Ok(complex);
+            };
+            self.skip_whitespace();
+            complex.combinators.push(combinator);
+            complex.compounds.push(self.parse_compound()?);
+        }
+    }
+
+    fn parse_compound(&mut self) -> Result<CompoundSelector, SelectorError> {
+        let start = self.pos;
+        let tag_name = if self.eat('*') {
+            None
+        } else if self.peek().is_some_and(is_identifier_start) {
+            Some(self.parse_identifier()?)
+        } else {
+            None
+        };
+
+        let mut conditions = Vec::new();
+        loop {
+            if self.eat('#') {
+                conditions.push(Condition::Id(self.parse_identifier()?));
+            } else if self.eat('.') {
+                conditions.push(Condition::Class(self.parse_identifier()?));
+            } else if self.eat('[') {
+                conditions.push(self.parse_attribute()?);
+            } else if self.eat(':') {
+                self.parse_pseudo_class(&mut conditions)?;
+            } else {
+                break;
+            }
+        }
+
+        if self.pos == start {
+            return Err(match self.peek() {
+                Some(c) => self.error(format!("expected a selector, found `{c}`")),
+                None => self.error("expected a selector"),
+            });
+        }
+        Ok(CompoundSelector {
+            tag_name,
+            conditions,
+        })
+    }
+
+    fn parse_attribute(&mut self) -> Result<Condition, SelectorError> {
+        self.skip_whitespace();
+        let name = self.parse_identifier()?;
+        self.skip_whitespace();
+        if self.eat(']') {
+            return Ok(Condition::Attribute {
+                name,
+                matcher: None,
+            });
+        }
+
+        let operator = if self.eat('=') {
+            AttributeOperator::Equals
+        } else {
+            let operator = match self.peek() {
+                Some('~') => AttributeOperator::Includes,
+                Some('|') => AttributeOperator::DashMatch,
+                Some('^') => AttributeOperator::Prefix,
+                Some('$') => AttributeOperator::Suffix,
+                Some('*') => AttributeOperator::Substring,
+                _ => return Err(self.error("expected an attribute operator or `]`")),
+            };
+            self.pos += 1;
+            if !self.eat('=') {
+                return Err(self.error("expected `=`"));
+            }
+            operator
+        };
+
+        self.skip_whitespace();
+        let value = match self.peek() {
+            Some(quote @ ('"' | '\'')) => self.parse_string(quote)?,
+            _ => self.parse_identifier()?,
+        };
+        self.skip_whitespace();
+        let ignore_case = matches!(self.peek(), Some('i' | 'I'));
+        if matches!(self.peek(), Some('i' | 'I' | 's' | 'S')) {
+            self.pos += 1;
+        }
+        self.skip_whitespace();
+        if !self.eat(']') {
+            return Err(self.error("expected `]`"));
+        }
+        Ok(Condition::Attribute {
+            name,
+            matcher: Some(AttributeMatcher {
+                operator,
+                value,
+                ignore_case,
+            }),
+        })
+    }
+
+    fn parse_pseudo_class(&mut self, conditions: &mut Vec<Condition>) -> Result<(), SelectorError> {
+        let start = self.pos;
+        let name = self.parse_identifier()?.to_ascii_lowercase();
+        let first = Condition::NthChild {
+            a: 0,
+            b: 1,
+            from_end: false,
+        };
+        let last = Condition::NthChild {
+            a: 0,
+            b: 1,
+            from_end: true,
+        };
+        match name.as_str() {
+            "first-child" => conditions.push(first),
+            "last-child" => conditions.push(last),
+            "only-child" => conditions.extend([first, last]),
+            "nth-child" | "nth-last-child" => {
+                let from_end = name == "nth-last-child";
+                let (a, b) = self.parse_arguments(Self::parse_nth)?;
+                conditions.push(Condition::NthChild { a, b, from_end });
+            }
+            "not" => conditions.push(Condition::Not(self.parse_arguments(Self::parse_list)?)),
+            _ => {
+                self.pos = start;
+                return Err(self.error(format!("unsupported pseudo-class `:{name}`")));
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_arguments<T>(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<T, SelectorError>,
+    ) -> Result<T, SelectorError> {
+        if !self.eat('(') {
+            return Err(self.error("expected `(`"));
+        }
+        self.skip_whitespace();
+        let arguments = parse(self)?;
+        self.skip_whitespace();
+        if !self.eat(')') {
+            return Err(self.error("expected `)`"));
+        }
+        Ok(arguments)
+    }
+
+    /// Parses `an+b`, `odd` or `even`.
+    fn parse_nth(&mut self) -> Result<(i64, i64), SelectorError> {
+        let end = self
+            .rest()
+            .find(')')
+            .map_or(self.selector.len(), |end| self.pos + end);
+        let nth: String = self.selector[self.pos..end]
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect::<String>()
+            .to_ascii_lowercase();
+        let value = match nth.as_str() {
+            "odd" => Some((2, 1)),
+            "even" => Some((2, 0)),
+            _ => match nth.split_once('n') {
+                Some((a, b)) => {
+                    let a = match a {
+                        "" | "+" => Some(1),
+                        "-" => Some(-1),
+                        a => a.parse().ok(),
+                    };
+                    let b = match b {
+                        "" => Some(0),
+                        b if b.starts_with(['+', '-']) => b.parse().ok(),
+                        _ => None,
+                    };
+                    a.zip(b)
+                }
+                None => nth.parse().ok().map(|b| (0, b)),
+            },
+        };
+        match value {
+            Some(value) => {
+                self.pos = end;
+                Ok(value)
+            }
+            None => Err(self.error(format!("invalid `an+b` expression `{nth}`"))),
+        }
+    }
+
+    fn parse_identifier(&mut self) -> Result<String, SelectorError> {
+        let mut identifier = String::new();
+        while let Some(c) = self.peek() {
+            if c == '\\' {
+                self.pos += 1;
+                match self.peek() {
+                    Some(escaped) => {
+                        identifier.push(escaped);
+                        self.pos += escaped.len_utf8();
+                    }
+                    None => return Err(self.error("unfinished escape")),
+                }
+            } else if is_identifier_char(c) {
+                identifier.push(c);
+                self.pos += c.len_utf8();
+            } else {
+                break;
+            }
+        }
+        if identifier.is_empty() {
+            return Err(match self.peek() {
+                Some(c) => self.error(format!("expected a name, found `{c}`")),
+                None => self.error("expected a name"),
+            });
+        }
+        Ok(identifier)
+    }
+
+    fn parse_string(&mut self, quote: char) -> Result<String, SelectorError> {
+        let start = self.pos;
+        self.pos += 1;
+        let mut string = String::new();
+        while let Some(c) = self.peek() {
+            self.pos += c.len_utf8();
+            match c {
+                c if c == quote => return Ok(string),
+                '\\' => {
+                    if let Some(escaped) = self.peek() {
+                        string.push(escaped);
+                        self.pos += escaped.len_utf8();
+                    }
+                }
+                c => string.push(c),
+            }
+        }
+        self.pos = start;
+        Err(self.error("unclosed string"))
+    }
+
+    fn rest(&self) -> &str {
+        &self.selector[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns whether there was any whitespace.
+    fn skip_whitespace(&mut self) -> bool {
+        let len = self.rest().len() - self.rest().trim_start().len();
+        self.pos += len;
+        len > 0
+    }
+
+    fn error(&self, message: impl Into<String>) -> SelectorError {
+        SelectorError {
+            message: message.into(),
+            column: self.selector[..self.pos].chars().count() + 1,
+        }
+    }
+}
+
+fn is_identifier_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_' || c == '-' || c == '\\' || !c.is_ascii()
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-' || !c.is_ascii()
+}
+
+/// An element of the tree, with what the combinators and `:nth-child()` need to know about it.
+struct Entry<'a> {
+    element: &'a ElementData,
+    parent: Option<usize>,
+    /// The previous element sibling.
+    previous: Option<usize>,
+    /// Starts at 1 and counts element siblings only.
+    position: usize,
+    siblings: usize,
+}
+
+/// Collects the elements in document order.
+fn collect_entries<'a>(
+    fragment: &'a HtmlFragment,
+    parent: Option<usize>,
+    entries: &mut Vec<Entry<'a>>,
+) {
+    let elements = fragment.iter_expanded().filter_map(|node| match node {
+        Node::Element(element) => Some(element),
+        _ => None,
+    });
+    let siblings = elements.clone().count();
+    let mut previous = None;
+    for (i, element) in elements.enumerate() {
+        let index = entries.len();
+        entries.push(Entry {
+            element,
+            parent,
+            previous,
+            position: i + 1,
+            siblings,
+        });
+        previous = Some(index);
+        collect_entries(&element.children, Some(index), entries);
+    }
+}
+
+/// Returns whether each element of `fragment`, in document order, matches `selector`.
+fn match_elements(fragment: &HtmlFragment, selector: &Selector) -> Vec<bool> {
+    let mut entries = Vec::new();
+    collect_entries(fragment, None, &mut entries);
+    match_entries(&entries, selector)
+}
+
+fn match_entries(entries: &[Entry], selector: &Selector) -> Vec<bool> {
+    (0..entries.len())
+        .map(|index| matches_any(entries, index, &selector.alternatives))
+        .collect()
+}
+
+fn matches_any(entries: &[Entry], index: usize, alternatives: &[ComplexSelector]) -> bool {
+    alternatives
+        .iter()
+        .any(|complex| matches_complex(entries, index, complex, complex.compounds.len() - 1))
+}
+
+/// Returns whether the element at `index` matches the compound selector at `k`
+/// and the ones before it.
+fn matches_complex(entries: &[Entry], index: usize, complex: &ComplexSelector, k: usize) -> bool {
+    if !matches_compound(entries, index, &complex.compounds[k]) {
+        return false;
+    }
+    if k == 0 {
+        return true;
+    }
+
+    let entry = &entries[index];
+    match complex.combinators[k - 1] {
+        Combinator::Child => entry
+            .parent
+            .is_some_and(|parent| matches_complex(entries, parent, complex, k - 1)),
+        Combinator::NextSibling => entry
+            .previous
+            .is_some_and(|previous| matches_complex(entries, previous, complex, k - 1)),
+        Combinator::Descendant => {
+            std::iter::successors(entry.parent, |&ancestor| entries[ancestor].parent)
+                .any(|ancestor| matches_complex(entries, ancestor, complex, k - 1))
+        }
+        Combinator::SubsequentSibling => {
+            std::iter::successors(entry.previous, |&sibling| entries[sibling].previous)
+                .any(|sibling| matches_complex(entries, sibling, complex, k - 1))
+        }
+    }
+}
+
+fn matches_compound(entries: &[Entry], index: usize, compound: &CompoundSelector) -> bool {
+    let entry = &entries[index];
+    let element = entry.element;
+    if let Some(tag_name) = &compound.tag_name
+        && !element.tag_name.eq_ignore_ascii_case(tag_name)
+    {
+        return false;
+    }
+
+    compound.conditions.iter().all(|condition| match condition {
+        Condition::Id(id) => {
+            attribute_value(&element.attributes, "id").is_some_and(|value| value == *id)
+        }
+        Condition::Class(class) => attribute_value(&element.attributes, "class")
+            .is_some_and(|value| value.split_whitespace().any(|c| c == class)),
+        Condition::Attribute { name, matcher } => {
+            match attribute_value(&element.attributes, name) {
+                Some(value) => matcher
+                    .as_ref()
+                    .is_none_or(|matcher| matches_attribute(&value, matcher)),
+                None => false,
+            }
+        }
+        Condition::NthChild { a, b, from_end } => {
+            let position = if *from_end {
+                entry.siblings - entry.position + 1
+            } else {
+                entry.position
+            };
+            matches_nth(*a, *b, position as i64)
+        }
+        Condition::Not(alternatives) => !matches_any(entries, index, alternatives),
+    })
+}
+
+fn matches_attribute(value: &str, matcher: &AttributeMatcher) -> bool {
+    let (value, expected) = if matcher.ignore_case {
+        (value.to_lowercase(), matcher.value.to_lowercase())
+    } else {
+        (value.to_owned(), matcher.value.clone())
+    };
+    match matcher.operator {
+        AttributeOperator::Equals => value == expected,
+        AttributeOperator::Includes => value.split_whitespace().any(|word| word == expected),
+        AttributeOperator::DashMatch => {
+            value == expected || value.starts_with(&format!("{expected}-"))
+        }
+        AttributeOperator::Prefix => !expected.is_empty() && value.starts_with(&expected),
+        AttributeOperator::Suffix => !expected.is_empty() && value.ends_with(&expected),
+        AttributeOperator::Substring => !expected.is_empty() && value.contains(&expected),
+    }
+}
+
+/// Whether `position` is `a * n + b` for some `n >= 0`.
+fn matches_nth(a: i64, b: i64, position: i64) -> bool {
+    if a == 0 {
+        position == b
+    } else {
+        let n = position - b;
+        n % a == 0 && n / a >= 0
+    }
+}
+
+/// The unescaped value of an attribute. Values are stored escaped for HTML.
+fn attribute_value(attributes: &[Attribute], name: &str) -> Option<String> {
+    attributes
+        .iter()
+        .find(|attr| attr.name.eq_ignore_ascii_case(name))
+        .map(|attr| unescape_attribute(attr.value.as_deref().unwrap_or("")))
+}
+
+/// The elements matched by [HtmlFragment::select], in document order.
+pub struct Select<'a> {
+    iter: std::vec::IntoIter<&'a ElementData>,
+}
+
+impl<'a> Iterator for Select<'a> {
+    type Item = &'a ElementData;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next()
+    }
+}
+
+pub(crate) fn select<'a>(fragment: &'a HtmlFragment, selector: &Selector) -> Select<'a> {
+    let mut entries = Vec::new();
+    collect_entries(fragment, None, &mut entries);
+    let matched = match_entries(&entries, selector);
+    let elements: Vec<&ElementData> = entries
+        .into_iter()
+        .zip(matched)
+        .filter(|(_, matched)| *matched)
+        .map(|(entry, _)| entry.element)
+        .collect();
+    Select {
+        iter: elements.into_iter(),
+    }
+}
+
+/// A matched element of [HtmlFragment::select_mut]. Matched elements may be nested,
+/// so only the element itself can be modified, not its children. Like in selectors, attribute
+/// names are compared ignoring case.
+pub struct ElementMut<'a> {
+    pub tag_name: &'a mut String,
+    pub attributes: &'a mut Vec<Attribute>,
+    pub self_closing: &'a mut bool,
+}
+
+impl ElementMut<'_> {
+    pub fn has_attribute(&self, name: &str) -> bool {
+        self.attributes
+            .iter()
+            .any(|attr| attr.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Sets the value of an attribute, which is escaped for HTML. An existing attribute with the
+    /// name is replaced where it is.
+    pub fn set_attribute(&mut self, name: String, value: String) {
+        let value = Some(htmlize::escape_attribute(value).into_owned());
+        match self
+            .attributes
+            .iter_mut()
+            .find(|attr| attr.name.eq_ignore_ascii_case(&name))
+        {
+            Some(attr) => attr.value = value,
+            None => self.attributes.push(Attribute { name, value }),
+        }
+    }
+
+    pub fn remove_attribute(&mut self, name: &str) {
+        self.attributes
+            .retain(|attr| !attr.name.eq_ignore_ascii_case(name));
+    }
+
+    /// The unescaped value of an attribute.
+    pub fn get_attribute(&self, name: &str) -> Option<String> {
+        attribute_value(self.attributes, name)
+    }
+}
+
+/// The elements matched by [HtmlFragment::select_mut], in document order.
+pub struct SelectMut<'a> {
+    iter: std::vec::IntoIter<ElementMut<'a>>,
+}
+
+impl<'a> Iterator for SelectMut<'a> {
+    type Item = ElementMut<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next()
+    }
+}
+
+pub(crate) fn select_mut<'a>(fragment: &'a mut HtmlFragment, selector: &Selector) -> SelectMut<'a> {
+    fragment.expand_markup();
+    let matched = match_elements(fragment, selector);
+    let mut elements = Vec::new();
+    collect_matched_mut(fragment, &matched, &mut 0, &mut elements);
+    SelectMut {
+        iter: elements.into_iter(),
+    }
+}
+
+/// Walks the elements in the same order as [collect_entries], splitting every element into
+/// its fields so that a matched element and its matched descendants can be borrowed together.
+fn collect_matched_mut<'a>(
+    fragment: &'a mut HtmlFragment,
+    matched: &[bool],
+    index: &mut usize,
+    elements: &mut Vec<ElementMut<'a>>,
+) {
+    for node in fragment.iter_mut() {
+        let Node::Element(ElementData {
+            tag_name,
+            attributes,
+            children,
+            self_closing,
+        }) = node
+        else {
+            continue;
+        };
+        if matched[*index] {
+            elements.push(ElementMut {
+                tag_name,
+                attributes,
+                self_closing,
+            });
+        }
+        *index += 1;
+        collect_matched_mut(children, matched, index, elements);
+    }
+}
@@ -0,0 +1,218 @@
+//! Traversal of every node of an [HtmlFragment], not only the top-level ones.
+//!
+//! [HtmlFragment::iter_depth_first] and [HtmlFragment::iter_breadth_first] iterate over the
+//! nodes with their parent and depth. [Visitor] and [VisitorMut] are called when entering
+//! and leaving every element, and for every other node.
+//!
+//! The markup the [html](crate::html) macro renders at compile time is traversed as the nodes
+//! it is rendered from. [HtmlFragment::visit_mut] calls [HtmlFragment::expand_markup] first
+//! to modify them.
+
+use std::collections::VecDeque;
+
+use crate::{ElementData, ExpandedIter, HtmlFragment, Node};
+
+/// A node found by [HtmlFragment::iter_depth_first] or [HtmlFragment::iter_breadth_first].
+#[derive(Clone, Copy, Debug)]
+pub struct NodeRef<'a> {
+    pub node: &'a Node,
+    /// `None` for the top-level nodes.
+    pub parent: Option<&'a ElementData>,
+    /// 0 for the top-level nodes.
+    pub depth: usize,
+}
+
+/// Iterates over the nodes in document order: every element is followed by its descendants.
+pub struct DepthFirst<'a> {
+    stack: Vec<(ExpandedIter<'a>, Option<&'a ElementData>, usize)>,
+}
+
+impl<'a> DepthFirst<'a> {
+    pub(crate) fn new(fragment: &'a HtmlFragment) -> Self {
+        DepthFirst {
+            stack: vec![(fragment.iter_expanded(), None, 0)],
+        }
+    }
+}
+
+impl<'a> Iterator for DepthFirst<'a> {
+    type Item = NodeRef<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (nodes, parent, depth) = self.stack.last_mut()?;
+            let (parent, depth) = (*parent, *depth);
+            let Some(node) = nodes.next() else {
+                self.stack.pop();
+                continue;
+            };
+            if let Node::Element(element) = node {
+                self.stack
+                    .push((element.children.iter_expanded(), Some(element), depth + 1));
+            }
+            return Some(NodeRef {
+                node,
+                parent,
+                depth,
+            });
+        }
+    }
+}
+
+/// Iterates over the nodes level by level: all the top-level nodes first, then their children,
+/// and so on.
+pub struct BreadthFirst<'a> {
+    queue: VecDeque<NodeRef<'a>>,
+}
+
+impl<'a> BreadthFirst<'a> {
+    pub(crate) fn new(fragment: &'a HtmlFragment) -> Self {
+        BreadthFirst {
+            queue: fragment
+                .iter_expanded()
+                .map(|node| NodeRef {
+                    node,
+                    parent: None,
+                    depth: 0,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl<'a> Iterator for BreadthFirst<'a> {
+    type Item = NodeRef<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = self.queue.pop_front()?;
+        if let Node::Element(element) = next.node {
+            self.queue
+                .extend(element.children.iter_expanded().map(|node| NodeRef {
+                    node,
+                    parent: Some(element),
+                    depth: next.depth + 1,
+                }));
+        }
+        Some(next)
+    }
+}
+
+/// Called for every node by [HtmlFragment::visit], in document order.
+/// Every method does nothing by default.
+///
+/// ```
+/// # use hypersynthetic::{html, ElementData, visit::Visitor};
+/// #[derive(Default)]
+/// struct Outline {
+///     depth: usize,
+///     lines: Vec<String>,
+/// }
+///
+/// impl Visitor for Outline {
+///     fn enter_element(&mut self, element: &ElementData) {
+///         self.lines.push(format!("{}{}", "  ".repeat(self.depth), element.tag_name));
+///         self.depth += 1;
+///     }
+///
+///     fn leave_element(&mut self, _element: &ElementData) {
+///         self.depth -= 1;
+///     }
+/// }
+///
+/// let page = html! {
+///     <ul><li>"One"</li><li>"Two"</li></ul>
+/// };
+/// let mut outline = Outline::default();
+/// page.visit(&mut outline);
+/// assert_eq!(outline.lines, ["ul", "  li", "  li"]);
+/// ```
+pub trait Visitor {
+    /// Called before the children of the element are visited.
+    fn enter_element(&mut self, _element: &ElementData) {}
+
+    /// Called after the children of the element are visited.
+    fn leave_element(&mut self, _element: &ElementData) {}
+
+    fn visit_text(&mut self, _text: &str) {}
+
+    fn visit_doctype(&mut self) {}
+
+    /// The content of the comment, between `<!--` and `-->`.
+    fn visit_comment(&mut self, _text: &str) {}
+}
+
+/// Called for every node by [HtmlFragment::visit_mut], in document order.
+/// Every method does nothing by default.
+///
+/// Children that [VisitorMut::enter_element] adds or changes are visited afterwards.
+///
+/// ```
+/// # use hypersynthetic::{html, ElementData, visit::VisitorMut};
+/// struct LazyImages;
+///
+/// impl VisitorMut for LazyImages {
+///     fn enter_element(&mut self, element: &mut ElementData) {
+///         if element.tag_name == "img" && !element.has_attribute("loading") {
+///             element.set_attribute("loading".to_owned(), "lazy".to_owned());
+///         }
+///     }
+/// }
+///
+/// let mut page = html! {
+///     <article><p><img src="a.png" /></p></article>
+/// };
+/// page.visit_mut(&mut LazyImages);
+/// assert_eq!(
+///     page.to_string(),
+///     r#"<article><p><img src="a.png" loading="lazy" /></p></article>"#
+/// );
+/// ```
+pub trait VisitorMut {
+    /// Called before the children of the element are visited.
+    fn enter_element(&mut self, _element: &mut ElementData) {}
+
+    /// Called after the children of the element are visited.
+    fn leave_element(&mut self, _element: &mut ElementData) {}
+
+    /// The text is escaped for HTML, like in [Node::Text].
+    fn visit_text(&mut self, _text: &mut String) {}
+
+    fn visit_doctype(&mut self) {}
+
+    /// The text is escaped for a comment, like in [Node::Comment].
+    fn visit_comment(&mut self, _text: &mut String) {}
+}
+
+pub(crate) fn visit(fragment: &HtmlFragment, visitor: &mut (impl Visitor + ?Sized)) {
+    for node in fragment.iter_expanded() {
+        match node {
+            Node::Element(element) => {
+                visitor.enter_element(element);
+                visit(&element.children, visitor);
+                visitor.leave_element(element);
+            }
+            Node::Text(text) => visitor.visit_text(text),
+            Node::DocType => visitor.visit_doctype(),
+            Node::Comment(text) => visitor.visit_comment(text),
+            // Static markup is expanded by `iter_expanded`
+            Node::Static(_) | Node::Pending(_) => {}
+        }
+    }
+}
+
+/// Expects markup to be expanded already.
+pub(crate) fn visit_mut(fragment: &mut HtmlFragment, visitor: &mut (impl VisitorMut + ?Sized)) {
+    for node in fragment {
+        match node {
+            Node::Element(element) => {
+                visitor.enter_element(element);
+                visit_mut(&mut element.children, visitor);
+                visitor.leave_element(element);
+            }
+            Node::Text(text) => visitor.visit_text(text),
+            Node::DocType => visitor.visit_doctype(),
+            Node::Comment(text) => visitor.visit_comment(text),
+            Node::Static(_) | Node::Pending(_) => {}
+        }
+    }
+}
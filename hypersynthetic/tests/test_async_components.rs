@@ -0,0 +1,152 @@
+use std::time::Duration;
+
+use hypersynthetic::prelude::*;
+
+#[derive(Clone)]
+struct Locale(&'static str);
+
+#[component]
+async fn Delayed(millis: u64, text: String) -> HtmlFragment {
+    tokio::time::sleep(Duration::from_millis(millis)).await;
+    html! {
+        <span>{text}</span>
+    }
+}
+
+#[component]
+fn Static(text: &str) -> HtmlFragment {
+    html! {
+        <em>{text}</em>
+    }
+}
+
+#[component]
+async fn Panel(children: HtmlFragment, title: String) -> HtmlFragment {
+    let heading = async { title.to_uppercase() }.await;
+    html! {
+        <section>
+            <h2>{heading}</h2>
+            {{ children }}
+        </section>
+    }
+}
+
+#[component]
+async fn Greeting() -> HtmlFragment {
+    tokio::task::yield_now().await;
+    let locale = use_context::<Locale>().map_or("en", |locale| locale.0);
+    html_async! {
+        <p lang={locale}><Static text="hi" /></p>
+    }
+    .await
+}
+
+#[tokio::test]
+async fn test_async_components() {
+    let result = html_async! {
+        <div>
+            <Delayed millis={5} text={"a".to_string()} />
+            <Static text="b" />
+        </div>
+    }
+    .await;
+
+    assert_eq!(result.to_string(), "<div><span>a</span><em>b</em></div>");
+}
+
+#[tokio::test]
+async fn test_async_components_run_concurrently() {
+    let started = tokio::time::Instant::now();
+    let result = html_async! {
+        <Delayed :for={text in ["1", "2", "3", "4"]} millis={100} text={text.to_string()} />
+    }
+    .await;
+
+    assert_eq!(
+        result.to_string(),
+        "<span>1</span><span>2</span><span>3</span><span>4</span>"
+    );
+    assert!(started.elapsed() < Duration::from_millis(300));
+}
+
+#[tokio::test]
+async fn test_async_components_with_slots() {
+    let result = html_async! {
+        <Panel title={"news".to_string()}>
+            <Delayed millis={1} text={"inside".to_string()} />
+        </Panel>
+    }
+    .await;
+
+    assert_eq!(
+        result.to_string(),
+        "<section><h2>NEWS</h2><span>inside</span></section>"
+    );
+}
+
+#[tokio::test]
+async fn test_async_components_are_rendered_into_the_tree() {
+    let result = html_async! {
+        <Panel title={"news".to_string()}>
+            <Delayed millis={1} text={"inside".to_string()} />
+        </Panel>
+    }
+    .await;
+
+    let span = result.select("section > span").next().unwrap();
+    assert_eq!(span.children.to_string(), "inside");
+}
+
+#[component]
+fn Twice(children: HtmlFragment) -> HtmlFragment {
+    html! {
+        {{ children.clone() }}
+        {{ children }}
+    }
+}
+
+#[tokio::test]
+async fn test_async_components_in_copied_children() {
+    let result = html_async! {
+        <Twice>
+            <Panel title={"news".to_string()}>
+                <Delayed millis={1} text={"inside".to_string()} />
+            </Panel>
+        </Twice>
+    }
+    .await;
+
+    assert_eq!(
+        result.to_string(),
+        "<section><h2>NEWS</h2><span>inside</span></section>".repeat(2)
+    );
+}
+
+#[tokio::test]
+async fn test_async_components_see_context() {
+    let result = html_async! {
+        <Provider value={Locale("de")}>
+            <Greeting />
+        </Provider>
+        <Greeting />
+    }
+    .await;
+
+    assert_eq!(
+        result.to_string(),
+        "<p lang=\"de\"><em>hi</em></p><p lang=\"en\"><em>hi</em></p>"
+    );
+}
+
+#[tokio::test]
+async fn test_render_future_is_send() {
+    let handle = tokio::spawn(async {
+        html_async! {
+            <Delayed millis={1} text={"spawned".to_string()} />
+        }
+        .await
+        .to_string()
+    });
+
+    assert_eq!(handle.await.unwrap(), "<span>spawned</span>");
+}
@@ -0,0 +1,188 @@
+use hypersynthetic::escape::Trusted;
+use hypersynthetic::prelude::*;
+
+#[test]
+fn test_url_with_unsafe_scheme_is_replaced() {
+    let url = "javascript:alert(1)";
+    let result = html! {
+        <a href={url}>"Click"</a>
+    };
+
+    assert_eq!(result.to_string(), r#"<a href="about:invalid">Click</a>"#);
+}
+
+#[test]
+fn test_safe_and_relative_urls_are_kept() {
+    let render = |url: &str| html! { <img src={url} /> }.to_string();
+
+    assert_eq!(
+        render("https://example.com/a.png"),
+        r#"<img src="https://example.com/a.png" />"#
+    );
+    assert_eq!(render("/a.png?v=1:2"), r#"<img src="/a.png?v=1:2" />"#);
+    assert_eq!(
+        render("mailto:me@example.com"),
+        r#"<img src="mailto:me@example.com" />"#
+    );
+    assert_eq!(render(" java\tscript:x"), r#"<img src="about:invalid" />"#);
+}
+
+#[test]
+fn test_interpolated_url_is_checked_when_it_can_change_the_scheme() {
+    let scheme = "javascript";
+    let path = "javascript:alert(1)";
+    let result = html! {
+        <a href="{scheme}:alert(1)">"A"</a>
+        <a href="/users/{path}">"B"</a>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        r#"<a href="about:invalid">A</a><a href="/users/javascript:alert(1)">B</a>"#
+    );
+}
+
+#[test]
+fn test_optional_url() {
+    let url: Option<&str> = Some("data:text/html,x");
+    let missing: Option<&str> = None;
+    let result = html! {
+        <form action={url}><button formaction={missing}>"Go"</button></form>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        r#"<form action="about:invalid"><button>Go</button></form>"#
+    );
+}
+
+#[test]
+fn test_event_handler_values_are_js_escaped() {
+    let name = "'); alert('x";
+    let result = html! {
+        <button onclick="greet('{name}')">"Hi"</button>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        r#"<button onclick="greet('\'); alert(\'x')">Hi</button>"#
+    );
+}
+
+#[test]
+fn test_whole_event_handler_value_becomes_a_string_literal() {
+    let handler = "alert(1)".to_string();
+    let missing: Option<String> = None;
+    let result = html! {
+        <button onclick={handler} onfocus={missing}>"Hi"</button>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        r#"<button onclick="&quot;alert(1)&quot;">Hi</button>"#
+    );
+}
+
+#[test]
+fn test_only_event_handler_names_are_javascript() {
+    let value = "a'b";
+    let result = html! {
+        <li only="{value}" one={value} onclick="f('{value}')">"A"</li>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        r#"<li only="a'b" one="a'b" onclick="f('a\'b')">A</li>"#
+    );
+}
+
+#[test]
+fn test_whole_style_value_only_escapes_values() {
+    let style = "color: red; background: url(x)";
+    let width = "1px; color: blue";
+    let result = html! {
+        <p style={style}>"A"</p>
+        <p style={style} style:width={width}>"B"</p>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        r#"<p style="color: red; background: url\28 x\29 ;">A</p><p style="color: red; background: url\28 x\29; width: 1px\3B  color\3A  blue;">B</p>"#
+    );
+}
+
+#[test]
+fn test_spread_attributes_are_escaped_for_their_context() {
+    let mut attrs = Attributes::new();
+    attrs.set("href", "javascript:alert(1)");
+    attrs.set("onclick", "alert(1)");
+    attrs.set_trusted("onmouseover", "go()");
+    let pairs = [("style", "color: red; top: expression(x)")];
+    let result = html! {
+        <a {..attrs} {..pairs}>"A"</a>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        r#"<a href="about:invalid" onclick="&quot;alert(1)&quot;" onmouseover="go()" style="color: red; top: expression\28 x\29 ;">A</a>"#
+    );
+}
+
+#[test]
+fn test_dynamic_attribute_names_are_escaped_for_their_context() {
+    let (href, onclick) = ("href", "onclick");
+    let (url, name) = ("javascript:alert(1)", "'); alert('");
+    let result = html! {
+        <a {href}={url} {onclick}="greet('{name}')">"A"</a>
+        <a {href}="{url}">"B"</a>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        r#"<a href="about:invalid" onclick="greet('\'); alert(\'')">A</a><a href="about:invalid">B</a>"#
+    );
+}
+
+#[test]
+fn test_style_values_are_css_escaped() {
+    let color = "red; background: url(x)";
+    let width = "10px";
+    let result = html! {
+        <p style="color: {color}" style:width={width}>"Hi"</p>
+        <p style="color: {color}">"Hi"</p>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        r#"<p style="color: red\3B  background\3A  url\28 x\29; width: 10px;">Hi</p><p style="color: red\3B  background\3A  url\28 x\29 ">Hi</p>"#
+    );
+}
+
+#[test]
+fn test_trusted_values_skip_context_escaping() {
+    let url = "javascript:void(0)";
+    let style = "color: red; margin: 0";
+    let handler: Option<Trusted<&str>> = Some(Trusted("go('\"')"));
+    let args = Trusted("1, 2");
+    let result = html! {
+        <a href={Trusted(url)} style={Trusted(style)} onclick={handler}>"A"</a>
+        <a onclick="go({args})">"B"</a>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        r#"<a href="javascript:void(0)" style="color: red; margin: 0" onclick="go('&quot;')">A</a><a onclick="go(1, 2)">B</a>"#
+    );
+}
+
+#[test]
+fn test_literal_values_are_not_changed() {
+    let result = html! {
+        <a href="javascript:void(0)" onclick="go('x')" style="color: red">"A"</a>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        r#"<a href="javascript:void(0)" onclick="go('x')" style="color: red">A</a>"#
+    );
+}
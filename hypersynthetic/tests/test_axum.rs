@@ -37,3 +37,28 @@ async fn test_axum_response() {
     let body = response.text();
     assert_eq!(body, "<body><h1>Hello, world!</h1></body>");
 }
+
+#[cfg(feature = "axum")]
+#[tokio::test]
+async fn test_axum_async_response() {
+    use axum::{Router, routing::get};
+    use axum_test::{TestServer, TestServerConfig};
+    use hypersynthetic::prelude::*;
+
+    #[component]
+    async fn Title(text: String) -> HtmlFragment {
+        let text = async { text }.await;
+        html!(<h1>{text}</h1>)
+    }
+
+    async fn handler() -> HtmlFragment {
+        html_async!(<body><Title text={"Hello, async!".to_string()} /></body>).await
+    }
+
+    let app = Router::new().route("/", get(handler));
+    let server = TestServer::new_with_config(app, TestServerConfig::default()).unwrap();
+
+    let response = server.get("/").await;
+
+    assert_eq!(response.text(), "<body><h1>Hello, async!</h1></body>");
+}
@@ -0,0 +1,94 @@
+use hypersynthetic::prelude::*;
+
+#[test]
+fn test_bare_text() {
+    let result = html! {
+        <p>Hello, world!</p>
+    };
+
+    assert_eq!(result.to_string(), "<p>Hello, world!</p>");
+}
+
+#[test]
+fn test_bare_text_next_to_tags_and_expressions() {
+    let name = "Ferris";
+    let result = html! {
+        <p>Hello <b>{name}</b>, how are you?</p>
+        <p>Welcome back, {name}!</p>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        "<p>Hello <b>Ferris</b>, how are you?</p><p>Welcome back, Ferris!</p>"
+    );
+}
+
+#[test]
+fn test_bare_text_whitespace_collapsing() {
+    let result = html! {
+        <p>
+            Lorem   ipsum
+            dolor (sit amet) [consectetur]
+            <a href="/more">read more</a>
+        </p>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        "<p>Lorem ipsum dolor (sit amet) [consectetur]<a href=\"/more\">read more</a></p>"
+    );
+}
+
+#[test]
+fn test_bare_text_punctuation() {
+    let result = html! {
+        <p>Price: 9.99 - 19.99, e.g. 50% off; see #deals -> now.</p>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        "<p>Price: 9.99 - 19.99, e.g. 50% off; see #deals -&gt; now.</p>"
+    );
+}
+
+#[test]
+fn test_bare_text_keeps_character_references() {
+    let result = html! {
+        <p>Fish &amp; Chips&nbsp;&#8212; Salt & Vinegar</p>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        "<p>Fish &amp; Chips&nbsp;&#8212; Salt &amp; Vinegar</p>"
+    );
+}
+
+#[test]
+fn test_bare_text_keeps_references_without_semicolons() {
+    let result = html! {
+        <p>AT&T &copy 2024 &#169 && done</p>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        "<p>AT&T &copy 2024 &#169 &amp;&amp; done</p>"
+    );
+}
+
+#[test]
+fn test_bare_text_with_string_literals() {
+    let result = html! {
+        <p>Say "<hello>" twice</p>
+    };
+
+    assert_eq!(result.to_string(), "<p>Say &lt;hello&gt; twice</p>");
+}
+
+#[test]
+fn test_bare_text_on_top_level() {
+    let result = html! {
+        Just text <br /> and more
+    };
+
+    assert_eq!(result.to_string(), "Just text <br /> and more");
+}
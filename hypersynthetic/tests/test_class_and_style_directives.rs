@@ -0,0 +1,88 @@
+use hypersynthetic::prelude::*;
+
+#[test]
+fn test_class_directives() {
+    let render = |is_active: bool, is_disabled: bool| {
+        html! {
+            <button class="btn" class:active={is_active} class:btn-disabled={is_disabled}>"Go"</button>
+        }
+        .to_string()
+    };
+
+    assert_eq!(render(false, false), "<button class=\"btn\">Go</button>");
+    assert_eq!(
+        render(true, true),
+        "<button class=\"btn active btn-disabled\">Go</button>"
+    );
+}
+
+#[test]
+fn test_class_directives_without_static_class() {
+    let render = |is_active: bool| {
+        html! {
+            <li id="item" class:active={is_active}>"Item"</li>
+        }
+        .to_string()
+    };
+
+    assert_eq!(render(true), "<li id=\"item\" class=\"active\">Item</li>");
+    assert_eq!(render(false), "<li id=\"item\">Item</li>");
+}
+
+#[test]
+fn test_classes_are_deduplicated_and_normalized() {
+    let extra = "  card   shadow ";
+    let result = html! {
+        <div class:card={true} class="card {extra}" class:shadow={true}></div>
+    };
+
+    assert_eq!(result.to_string(), "<div class=\"card shadow\"></div>");
+}
+
+#[test]
+fn test_class_values_are_escaped() {
+    let extra = "a\"b";
+    let result = html! {
+        <div class={extra} class:c={true}></div>
+    };
+
+    assert_eq!(result.to_string(), "<div class=\"a&quot;b c\"></div>");
+}
+
+#[test]
+fn test_style_directives() {
+    let color = "red";
+    let width: Option<&str> = None;
+    let result = html! {
+        <p style="margin: 0; color: blue" style:color={color} style:width={width} style:font-size="12px">"Hi"</p>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        "<p style=\"margin: 0; color: red; font-size: 12px;\">Hi</p>"
+    );
+}
+
+#[test]
+fn test_style_directives_keep_urls_intact() {
+    let result = html! {
+        <div style="background: url('a;b.png')" style:display="none"></div>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        "<div style=\"background: url('a;b.png'); display: none;\"></div>"
+    );
+}
+
+#[test]
+fn test_element_without_directives_is_unchanged() {
+    let result = html! {
+        <div class="a  a" style="color:red"></div>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        "<div class=\"a  a\" style=\"color:red\"></div>"
+    );
+}
@@ -0,0 +1,109 @@
+use hypersynthetic::Node;
+use hypersynthetic::prelude::*;
+use hypersynthetic::pretty::PrettyOptions;
+use hypersynthetic::visit::{Visitor, VisitorMut};
+
+#[test]
+fn test_comment_node() {
+    let page = html! {
+        <!-- "License: MIT" -->
+        <p>"Hi"</p>
+    };
+
+    assert!(matches!(page.iter().next(), Some(Node::Comment(text)) if text == " License: MIT "));
+    assert_eq!(page.to_string(), "<!-- License: MIT --><p>Hi</p>");
+    assert_eq!(page.size_hint(), page.to_string().len());
+}
+
+#[test]
+fn test_comment_with_bare_tokens_and_expressions() {
+    let build = 42;
+    let page = html! {
+        <div>
+            <!-- TODO: remove (see issue 12) -->
+            <!--build {build}, "done"-->
+            <!---->
+        </div>
+    };
+
+    assert_eq!(
+        page.to_string(),
+        "<div><!-- TODO: remove (see issue 12) --><!-- build 42, done --><!----></div>"
+    );
+}
+
+#[test]
+fn test_comment_escaping() {
+    let text = "--><script>alert(1)</script><!--";
+    let trailing = "a-";
+    let page = html! {
+        <!-- {text} -->
+        <!-- "x -- y" -->
+        <!--{trailing}-->
+    };
+
+    assert_eq!(
+        page.to_string(),
+        "<!-- - -><script>alert(1)</script><!- - --><!-- x - - y --><!-- a- -->"
+    );
+}
+
+#[test]
+fn test_comments_in_static_markup() {
+    let mut page = html! {
+        <ul>
+            <!-- "items" -->
+            <li>"One"</li>
+        </ul>
+    };
+    page.expand_markup();
+
+    let ul = page.iter_elements().next().unwrap();
+    assert!(matches!(ul.children.iter().next(), Some(Node::Comment(text)) if text == " items "));
+    assert_eq!(page.to_string(), "<ul><!-- items --><li>One</li></ul>");
+}
+
+#[test]
+fn test_visit_comments() {
+    #[derive(Default)]
+    struct Comments(Vec<String>);
+
+    impl Visitor for Comments {
+        fn visit_comment(&mut self, text: &str) {
+            self.0.push(text.to_owned());
+        }
+    }
+
+    struct StripDebug;
+
+    impl VisitorMut for StripDebug {
+        fn visit_comment(&mut self, text: &mut String) {
+            if text.trim().starts_with("debug") {
+                text.clear();
+            }
+        }
+    }
+
+    let mut page = HtmlFragment::parse("<!-- a --><p>x<!-- debug: 1 --></p>").unwrap();
+    let mut comments = Comments::default();
+    page.visit(&mut comments);
+    assert_eq!(comments.0, [" a ", " debug: 1 "]);
+
+    page.visit_mut(&mut StripDebug);
+    assert_eq!(page.to_string(), "<!-- a --><p>x<!----></p>");
+}
+
+#[test]
+fn test_pretty_comments() {
+    let page = html! {
+        <!-- "header" -->
+        <div>
+            <p>"Text"<!-- "note" --></p>
+        </div>
+    };
+
+    assert_eq!(
+        page.to_pretty_string(&PrettyOptions::default()),
+        "<!-- header -->\n<div>\n  <p>Text<!-- note --></p>\n</div>"
+    );
+}
@@ -0,0 +1,91 @@
+use hypersynthetic::prelude::*;
+
+#[test]
+fn test_boolean_attributes() {
+    let render = |is_disabled: bool, is_checked: bool| {
+        html! {
+            <input type="checkbox" disabled={is_disabled} checked={is_checked} />
+        }
+        .to_string()
+    };
+
+    assert_eq!(render(true, false), "<input type=\"checkbox\" disabled />");
+    assert_eq!(render(false, true), "<input type=\"checkbox\" checked />");
+    assert_eq!(render(false, false), "<input type=\"checkbox\" />");
+}
+
+#[test]
+fn test_boolean_attribute_without_expression() {
+    let result = html! {
+        <details open class="faq"></details>
+    };
+
+    assert_eq!(result.to_string(), "<details open class=\"faq\"></details>");
+}
+
+#[test]
+fn test_toggled_attributes() {
+    let render = |boosted: bool| {
+        html! {
+            <a href="/next" hx-boost:if={boosted}>"Next"</a>
+        }
+        .to_string()
+    };
+
+    assert_eq!(render(true), "<a href=\"/next\" hx-boost>Next</a>");
+    assert_eq!(render(false), "<a href=\"/next\">Next</a>");
+}
+
+#[test]
+fn test_optional_attribute_values() {
+    let render = |title: Option<&str>, tab_index: Option<i32>| {
+        html! {
+            <div title={title} tabindex={tab_index}>"Hi"</div>
+        }
+        .to_string()
+    };
+
+    assert_eq!(
+        render(Some("<tooltip>"), Some(2)),
+        "<div title=\"&lt;tooltip&gt;\" tabindex=\"2\">Hi</div>"
+    );
+    assert_eq!(render(None, Some(-1)), "<div tabindex=\"-1\">Hi</div>");
+    assert_eq!(render(None, None), "<div>Hi</div>");
+}
+
+#[test]
+fn test_non_optional_expression_values_are_unchanged() {
+    let id = String::from("main");
+    let result = html! {
+        <div id={id} data-visible={true}></div>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        "<div id=\"main\" data-visible=\"true\"></div>"
+    );
+    // the value is only borrowed by the macro
+    assert_eq!(id, "main");
+}
+
+#[test]
+fn test_hidden_is_only_toggled_by_bool_values() {
+    let render = |hidden: bool, collapsed: Option<bool>, mode: Option<&str>| {
+        html! {
+            <p hidden={hidden}>"a"</p>
+            <p hidden={collapsed}>"b"</p>
+            <p hidden={mode}>"c"</p>
+            <p hidden={"until-found"}>"d"</p>
+        }
+        .to_string()
+    };
+
+    assert_eq!(
+        render(true, Some(true), Some("until-found")),
+        "<p hidden>a</p><p hidden>b</p><p hidden=\"until-found\">c</p><p hidden=\"until-found\">d</p>"
+    );
+    assert_eq!(
+        render(false, None, None),
+        "<p>a</p><p>b</p><p>c</p><p hidden=\"until-found\">d</p>"
+    );
+}
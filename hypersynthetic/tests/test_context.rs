@@ -0,0 +1,159 @@
+use hypersynthetic::context::{provide, with_context};
+use hypersynthetic::prelude::*;
+
+#[derive(Clone)]
+struct User {
+    name: String,
+}
+
+#[derive(Clone)]
+struct CsrfToken(&'static str);
+
+#[component]
+fn UserBadge() -> HtmlFragment {
+    let user = use_context::<User>();
+    html! {
+        <span :if={user.is_some()}>{user.unwrap().name}</span>
+    }
+}
+
+#[component]
+fn Form(children: HtmlFragment) -> HtmlFragment {
+    let token = use_context::<CsrfToken>().expect("no CSRF token");
+    html! {
+        <form method="post">
+            <input type="hidden" name="csrf" value={token.0} />
+            {{ children }}
+        </form>
+    }
+}
+
+#[component]
+fn Layout(children: HtmlFragment) -> HtmlFragment {
+    html! {
+        <main>{{ children }}</main>
+    }
+}
+
+#[test]
+fn test_context_reaches_nested_components() {
+    let user = User {
+        name: "Alice".to_string(),
+    };
+
+    let result = html! {
+        <Provider value={user}>
+            <Provider value={CsrfToken("t0k3n")}>
+                <Layout>
+                    <Form>
+                        <UserBadge />
+                    </Form>
+                </Layout>
+            </Provider>
+        </Provider>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        "<main><form method=\"post\"><input type=\"hidden\" name=\"csrf\" value=\"t0k3n\" /><span>Alice</span></form></main>"
+    );
+}
+
+#[test]
+fn test_context_is_scoped_to_provider() {
+    let result = html! {
+        <UserBadge />
+        <Provider value={User { name: "Bob".to_string() }}>
+            <UserBadge />
+        </Provider>
+        <UserBadge />
+    };
+
+    assert_eq!(result.to_string(), "<span>Bob</span>");
+    assert!(use_context::<User>().is_none());
+}
+
+#[test]
+fn test_inner_provider_shadows_outer() {
+    let result = html! {
+        <Provider value={User { name: "Outer".to_string() }}>
+            <UserBadge />
+            <Provider value={User { name: "Inner".to_string() }}>
+                <UserBadge />
+            </Provider>
+            <UserBadge />
+        </Provider>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        "<span>Outer</span><span>Inner</span><span>Outer</span>"
+    );
+}
+
+#[test]
+fn test_provider_with_for() {
+    let names = ["a", "b"];
+
+    let result = html! {
+        <Provider :for={name in names} value={User { name: name.to_string() }}>
+            <UserBadge />
+        </Provider>
+    };
+
+    assert_eq!(result.to_string(), "<span>a</span><span>b</span>");
+}
+
+#[test]
+fn test_context_around_render_call() {
+    let render = || html! { <UserBadge /> }.to_string();
+
+    let result = with_context(
+        User {
+            name: "Carol".to_string(),
+        },
+        render,
+    );
+    assert_eq!(result, "<span>Carol</span>");
+
+    let _guard = provide(User {
+        name: "Dave".to_string(),
+    });
+    assert_eq!(render(), "<span>Dave</span>");
+}
+
+#[test]
+fn test_guards_dropped_out_of_order() {
+    let render = || html! { <UserBadge /> }.to_string();
+    let user = |name: &str| User {
+        name: name.to_string(),
+    };
+
+    let outer = provide(user("Erin"));
+    let inner = provide(user("Frank"));
+    drop(outer);
+    assert_eq!(render(), "<span>Frank</span>");
+    drop(inner);
+    assert_eq!(render(), "");
+}
+
+#[component]
+fn Visits() -> HtmlFragment {
+    let visits = use_context::<std::rc::Rc<std::cell::Cell<u32>>>().expect("no counter");
+    visits.set(visits.get() + 1);
+    html! { <i>{visits.get()}</i> }
+}
+
+#[test]
+fn test_context_does_not_need_to_be_send() {
+    let visits = std::rc::Rc::new(std::cell::Cell::new(0u32));
+    let result = html! {
+        <Provider value={visits.clone()}>
+            <Visits />
+            <Visits />
+        </Provider>
+    };
+
+    assert_eq!(result.to_string(), "<i>1</i><i>2</i>");
+    assert_eq!(visits.get(), 2);
+}
@@ -0,0 +1,170 @@
+use std::fmt;
+use std::num::ParseIntError;
+
+use hypersynthetic::prelude::*;
+
+#[derive(Debug, PartialEq)]
+enum PageError {
+    Parse(String),
+    NotFound(u32),
+}
+
+impl From<ParseIntError> for PageError {
+    fn from(error: ParseIntError) -> Self {
+        PageError::Parse(error.to_string())
+    }
+}
+
+impl fmt::Display for PageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PageError::Parse(message) => write!(f, "parse error: {message}"),
+            PageError::NotFound(id) => write!(f, "user {id} not found"),
+        }
+    }
+}
+
+#[component]
+fn Count(input: &str) -> Result<HtmlFragment, ParseIntError> {
+    let count: u32 = input.parse()?;
+    Ok(html! {
+        <span>{count}</span>
+    })
+}
+
+#[component]
+fn User(id: u32) -> Result<HtmlFragment, PageError> {
+    if id != 1 {
+        return Err(PageError::NotFound(id));
+    }
+    Ok(html! {
+        <b>"Alice"</b>
+    })
+}
+
+#[component]
+fn Label(text: &str) -> HtmlFragment {
+    html! {
+        <i>{text}</i>
+    }
+}
+
+#[component]
+fn Card(children: HtmlFragment) -> Result<HtmlFragment, PageError> {
+    Ok(html! {
+        <div class="card">{{ children }}</div>
+    })
+}
+
+fn render_count(input: &str) -> Result<HtmlFragment, PageError> {
+    try_html! {
+        <p><Label text="Count:" /><Count input={input} /></p>
+    }
+}
+
+#[test]
+fn test_try_html() {
+    assert_eq!(
+        render_count("3").unwrap().to_string(),
+        "<p><i>Count:</i><span>3</span></p>"
+    );
+    assert_eq!(
+        render_count("x").unwrap_err(),
+        PageError::Parse("invalid digit found in string".to_string())
+    );
+}
+
+#[test]
+fn test_try_html_stops_at_first_error() {
+    let result: Result<HtmlFragment, PageError> = try_html! {
+        <User :for={id in [1, 2, 3]} id={id} />
+    };
+
+    assert_eq!(result.unwrap_err(), PageError::NotFound(2));
+}
+
+#[test]
+fn test_try_html_with_slots_and_question_mark() {
+    let render = |input: &str| -> Result<HtmlFragment, PageError> {
+        try_html! {
+            <Card>
+                <User id={input.parse()?} />
+            </Card>
+        }
+    };
+
+    assert_eq!(
+        render("1").unwrap().to_string(),
+        "<div class=\"card\"><b>Alice</b></div>"
+    );
+    assert_eq!(render("7").unwrap_err(), PageError::NotFound(7));
+    assert!(matches!(render("one"), Err(PageError::Parse(_))));
+}
+
+#[test]
+fn test_error_boundary() {
+    let render = |id: u32| {
+        html! {
+            <main>
+                <ErrorBoundary fallback={|error: PageError| html! { <p class="error">{error}</p> }}>
+                    <User id={id} />
+                    <Label text="after" />
+                </ErrorBoundary>
+            </main>
+        }
+    };
+
+    assert_eq!(
+        render(1).to_string(),
+        "<main><b>Alice</b><i>after</i></main>"
+    );
+    assert_eq!(
+        render(5).to_string(),
+        "<main><p class=\"error\">user 5 not found</p></main>"
+    );
+}
+
+#[test]
+fn test_nested_error_boundaries() {
+    let result = html! {
+        <ErrorBoundary fallback={|_: PageError| html! { "outer" }}>
+            <ErrorBoundary fallback={|error: ParseIntError| html! { <s>{error}</s> }}>
+                <Count input="nope" />
+            </ErrorBoundary>
+            <User id={1} />
+        </ErrorBoundary>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        "<s>invalid digit found in string</s><b>Alice</b>"
+    );
+}
+
+#[test]
+fn test_question_mark_returns_from_the_enclosing_function() {
+    fn render(input: &str) -> Result<HtmlFragment, String> {
+        let page = html! {
+            <ErrorBoundary fallback={|_: PageError| html! { "caught" }}>
+                <User id={input.parse().map_err(|_| "not a number".to_string())?} />
+            </ErrorBoundary>
+        };
+        Ok(page)
+    }
+
+    assert_eq!(render("5").unwrap().to_string(), "caught");
+    assert_eq!(render("five").unwrap_err(), "not a number");
+}
+
+#[test]
+fn test_return_in_try_html_returns_from_the_enclosing_function() {
+    fn render(ids: &[u32]) -> Result<HtmlFragment, PageError> {
+        try_html! {
+            <User :for={id in ids} id={if *id == 0 { return Ok(html! { "nobody" }) } else { *id }} />
+        }
+    }
+
+    assert_eq!(render(&[1]).unwrap().to_string(), "<b>Alice</b>");
+    assert_eq!(render(&[0, 7]).unwrap().to_string(), "nobody");
+    assert_eq!(render(&[7, 0]).unwrap_err(), PageError::NotFound(7));
+}
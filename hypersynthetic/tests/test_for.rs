@@ -100,3 +100,79 @@ fn test_for_parentheses() {
     let expected = "<div><p>1</p><p>one</p></div><div><p>2</p><p>two)</p></div>";
     assert_eq!(string_representation, expected);
 }
+
+#[test]
+fn test_for_with_loop_info() {
+    let names = ["a", "b", "c", "d"];
+    let result = html! {
+        <ul>
+            <li :for={name in names, row} class:first={row.first} class:last={row.last} class:odd={row.odd}>
+                {row.index}{name}
+            </li>
+        </ul>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        "<ul><li class=\"first\">0a</li><li class=\"odd\">1b</li><li>2c</li><li class=\"last odd\">3d</li></ul>"
+    );
+}
+
+#[test]
+fn test_for_with_loop_info_on_components_and_fragments() {
+    let pairs = vec![("x", 1), ("y", 2)];
+    let result = html! {
+        <Component :for={(name, value) in pairs, info} val1={name} val2={value * 10 + info.index as i32} />
+        <Fragment :for={n in 0..2, info}>
+            <b :if={info.even}>{n}</b>
+        </Fragment>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        "<div><p>x</p><p>11</p></div><div><p>y</p><p>22</p></div><b>0</b>"
+    );
+}
+
+#[test]
+fn test_for_with_empty() {
+    let render = |items: Vec<&str>| {
+        html! {
+            <ul>
+                <li :for={item in items}>{item}</li>
+                <li :empty class="empty">"No items"</li>
+            </ul>
+        }
+        .to_string()
+    };
+
+    assert_eq!(render(vec!["one"]), "<ul><li>one</li></ul>");
+    assert_eq!(render(vec![]), "<ul><li class=\"empty\">No items</li></ul>");
+}
+
+#[test]
+fn test_for_with_empty_evaluates_the_collection_once() {
+    let mut evaluations = 0;
+    let mut items = || {
+        evaluations += 1;
+        Vec::<i32>::new()
+    };
+    let result = html! {
+        <Component :for={n in items()} val1="n" val2={n} />
+        <p :empty>"Empty"</p>
+    };
+
+    assert_eq!(result.to_string(), "<p>Empty</p>");
+    assert_eq!(evaluations, 1);
+}
+
+#[test]
+fn test_for_with_loop_info_and_empty() {
+    let items: [u8; 0] = [];
+    let result = html! {
+        <span :for={item in items, info}>{info.index}{item}</span>
+        <em :empty>"none"</em>
+    };
+
+    assert_eq!(result.to_string(), "<em>none</em>");
+}
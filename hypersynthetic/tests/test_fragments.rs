@@ -0,0 +1,94 @@
+use hypersynthetic::prelude::*;
+
+#[test]
+fn test_anonymous_fragment() {
+    let result = html! {
+        <tr>
+            <>
+                <td>"a"</td>
+                <td>"b"</td>
+            </>
+        </tr>
+    };
+
+    assert_eq!(result.to_string(), "<tr><td>a</td><td>b</td></tr>");
+}
+
+#[test]
+fn test_fragment_with_for() {
+    let terms = [("HTML", "Markup"), ("CSS", "Styles")];
+
+    let result = html! {
+        <dl>
+            <Fragment :for={(term, definition) in terms}>
+                <dt>{term}</dt>
+                <dd>{definition}</dd>
+            </Fragment>
+        </dl>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        "<dl><dt>HTML</dt><dd>Markup</dd><dt>CSS</dt><dd>Styles</dd></dl>"
+    );
+}
+
+#[test]
+fn test_fragment_with_if_and_else() {
+    let render = |admin: bool| {
+        html! {
+            <tr>
+                <td>"Name"</td>
+                <Fragment :if={admin}>
+                    <td>"Edit"</td>
+                    <td>"Delete"</td>
+                </Fragment>
+                <Fragment :else>
+                    <td colspan="2">"Read only"</td>
+                </Fragment>
+            </tr>
+        }
+        .to_string()
+    };
+
+    assert_eq!(
+        render(true),
+        "<tr><td>Name</td><td>Edit</td><td>Delete</td></tr>"
+    );
+    assert_eq!(
+        render(false),
+        "<tr><td>Name</td><td colspan=\"2\">Read only</td></tr>"
+    );
+}
+
+#[test]
+fn test_fragment_as_match_arm() {
+    let user: Option<&str> = Some("Ferris");
+
+    let result = html! {
+        <template :match={user}>
+            <Fragment :case={Some(name)}>
+                <span>{name}</span>
+                <a href="/logout">"Log out"</a>
+            </Fragment>
+            <a :case={None} href="/login">"Log in"</a>
+        </template>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        "<span>Ferris</span><a href=\"/logout\">Log out</a>"
+    );
+}
+
+#[test]
+fn test_top_level_fragment_elements_are_mutable() {
+    let mut result = html! {
+        <>
+            <p>"one"</p>
+            <p>"two"</p>
+        </>
+    };
+
+    assert_eq!(result.iter_elements_mut().count(), 2);
+}
@@ -0,0 +1,163 @@
+use hypersynthetic::prelude::*;
+use std::fmt::Display;
+
+#[component]
+fn Table<T: Display>(rows: Vec<T>) -> HtmlFragment {
+    html! {
+        <table>
+            <tr :for={row in rows}><td>{row}</td></tr>
+        </table>
+    }
+}
+
+#[test]
+fn test_type_parameter_is_inferred() {
+    let result = html! {
+        <Table rows={vec![1, 2]} />
+    };
+    assert_eq!(
+        result.to_string(),
+        "<table><tr><td>1</td></tr><tr><td>2</td></tr></table>"
+    );
+}
+
+struct Row {
+    name: &'static str,
+}
+
+impl Display for Row {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "row {}", self.name)
+    }
+}
+
+#[test]
+fn test_turbofish() {
+    let result = html! {
+        <Table::<Row> rows={vec![Row { name: "a" }]} />
+    };
+    assert_eq!(result.to_string(), "<table><tr><td>row a</td></tr></table>");
+}
+
+#[test]
+fn test_generic_arguments_without_turbofish() {
+    let result = html! {
+        <Table<&str> rows={Vec::new()} />
+    };
+    assert_eq!(result.to_string(), "<table></table>");
+}
+
+#[component]
+fn Repeat<const N: usize>(text: &str) -> HtmlFragment {
+    html! {
+        <p :for={_ in 0..N}>{text}</p>
+    }
+}
+
+#[test]
+fn test_const_generic() {
+    let result = html! {
+        <Repeat::<3> text="echo" />
+    };
+    assert_eq!(result.to_string(), "<p>echo</p><p>echo</p><p>echo</p>");
+}
+
+#[component]
+fn Show(value: impl Display, label: &str) -> HtmlFragment {
+    html! {
+        <p>{label}": "{value}</p>
+    }
+}
+
+#[test]
+fn test_impl_trait_argument() {
+    let result = html! {
+        <Show value={42} label="answer" />
+        <Show value={"text"} label="string" />
+    };
+    assert_eq!(result.to_string(), "<p>answer: 42</p><p>string: text</p>");
+}
+
+#[component]
+fn Joined(items: Vec<impl Display>, separator: &'static str) -> HtmlFragment {
+    let text = items
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(separator);
+    html! {
+        <span>{text}</span>
+    }
+}
+
+#[test]
+fn test_nested_impl_trait_argument() {
+    let result = html! {
+        <Joined items={vec![1.5, 2.5]} separator=", " />
+    };
+    assert_eq!(result.to_string(), "<span>1.5, 2.5</span>");
+}
+
+#[component]
+fn Labeled<T>(label: &str, value: T) -> HtmlFragment
+where
+    T: Display + Clone,
+{
+    html! {
+        <label>{label}<input value={value.clone()} /></label>
+    }
+}
+
+#[test]
+fn test_lifetime_with_type_parameter_and_where_clause() {
+    let label = String::from("Age");
+    let result = html! {
+        <Labeled label={&label} value={30} />
+    };
+    assert_eq!(
+        result.to_string(),
+        "<label>Age<input value=\"30\" /></label>"
+    );
+}
+
+#[component]
+fn DefaultOf<T: Default + Display>() -> HtmlFragment {
+    html! {
+        <output>{T::default()}</output>
+    }
+}
+
+#[test]
+fn test_type_parameter_only_in_body() {
+    let result = html! {
+        <DefaultOf::<i32> />
+        <DefaultOf::<bool> />
+    };
+    assert_eq!(
+        result.to_string(),
+        "<output>0</output><output>false</output>"
+    );
+}
+
+#[component]
+fn List<T: Display, const ORDERED: bool>(children: HtmlFragment, items: &[T]) -> HtmlFragment {
+    html! {
+        <ol :if={ORDERED}><li :for={item in items}>{item}</li></ol>
+        <ul :else><li :for={item in items}>{item}</li></ul>
+        {{ children }}
+    }
+}
+
+#[test]
+fn test_generic_component_with_slot() {
+    let items = ["x", "y"];
+    let result = html! {
+        <List::<&str, true> items={&items}>
+            <p>"after"</p>
+        </List>
+    };
+    assert_eq!(
+        result.to_string(),
+        "<ol><li>x</li><li>y</li></ol><p>after</p>"
+    );
+}
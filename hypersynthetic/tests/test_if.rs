@@ -134,3 +134,105 @@ fn test_if_and_for_on_a_component() {
     let expected = "";
     assert_eq!(result.to_string(), expected);
 }
+
+#[test]
+fn test_else() {
+    for (truth, expected) in [(true, "<p>Yes</p>"), (false, "<span>No</span>")] {
+        let result = html! {
+            <p :if={truth}>"Yes"</p>
+            <span :else>"No"</span>
+        };
+
+        assert_eq!(result.to_string(), expected);
+    }
+}
+
+#[test]
+fn test_else_if() {
+    let render = |n: i32| {
+        html! {
+            <div>
+                <p :if={n < 0}>"Negative"</p>
+                <p :else-if={n == 0}>"Zero"</p>
+                <p :else-if={n < 10}>"Small"</p>
+                <p :else>"Large"</p>
+            </div>
+        }
+        .to_string()
+    };
+
+    assert_eq!(render(-5), "<div><p>Negative</p></div>");
+    assert_eq!(render(0), "<div><p>Zero</p></div>");
+    assert_eq!(render(7), "<div><p>Small</p></div>");
+    assert_eq!(render(42), "<div><p>Large</p></div>");
+}
+
+#[test]
+fn test_else_if_without_else() {
+    let render = |n: i32| {
+        html! {
+            <p :if={n == 1}>"One"</p>
+            <p :else-if={n == 2}>"Two"</p>
+        }
+        .to_string()
+    };
+
+    assert_eq!(render(1), "<p>One</p>");
+    assert_eq!(render(2), "<p>Two</p>");
+    assert_eq!(render(3), "");
+}
+
+#[test]
+fn test_condition_is_evaluated_once() {
+    let mut calls = 0;
+    let mut check = || {
+        calls += 1;
+        false
+    };
+
+    let result = html! {
+        <p :if={check()}>"Yes"</p>
+        <p :else>"No"</p>
+    };
+
+    assert_eq!(result.to_string(), "<p>No</p>");
+    assert_eq!(calls, 1);
+}
+
+#[test]
+fn test_else_on_components() {
+    let truth = false;
+
+    let result = html! {
+        <Dialog :if={truth} text="Yes" />
+        <Dialog2 :else>
+            <p>"No"</p>
+        </Dialog2>
+    };
+
+    assert_eq!(result.to_string(), "<div><p>No</p></div>");
+}
+
+#[test]
+fn test_else_with_for() {
+    let items = vec![1, 2];
+
+    let result = html! {
+        <p :if={items.is_empty()}>"Nothing"</p>
+        <p :else :for={i in &items}>{i}</p>
+    };
+
+    assert_eq!(result.to_string(), "<p>1</p><p>2</p>");
+}
+
+#[test]
+fn test_chains_are_independent() {
+    let result = html! {
+        <p :if={false}>"A"</p>
+        <p :else>"B"</p>
+        <p :if={true}>"C"</p>
+        <p :else>"D"</p>
+    };
+
+    assert_eq!(result.to_string(), "<p>B</p><p>C</p>");
+}
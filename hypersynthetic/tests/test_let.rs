@@ -0,0 +1,108 @@
+use std::cell::Cell;
+
+use hypersynthetic::prelude::*;
+
+struct User {
+    first_name: &'static str,
+    last_name: &'static str,
+}
+
+#[component]
+fn Greeting(name: String) -> HtmlFragment {
+    html! { <p>"Hello, {name}!"</p> }
+}
+
+#[test]
+fn test_let_in_element() {
+    let user = User {
+        first_name: "Ada",
+        last_name: "Lovelace",
+    };
+
+    let result = html! {
+        <div :let={full_name = format!("{} {}", user.first_name, user.last_name)} title={full_name}>
+            <h1>{full_name}</h1>
+            <p>"Welcome back, {full_name}"</p>
+        </div>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        r#"<div title="Ada Lovelace"><h1>Ada Lovelace</h1><p>Welcome back, Ada Lovelace</p></div>"#
+    );
+}
+
+#[test]
+fn test_let_is_evaluated_once() {
+    let calls = Cell::new(0);
+    let compute = || {
+        calls.set(calls.get() + 1);
+        42
+    };
+
+    let result = html! {
+        <ul :let={answer = compute()}>
+            <li>{answer}</li>
+            <li>{answer + 1}</li>
+        </ul>
+    };
+
+    assert_eq!(result.to_string(), "<ul><li>42</li><li>43</li></ul>");
+    assert_eq!(calls.get(), 1);
+}
+
+#[test]
+fn test_multiple_lets_and_patterns() {
+    let point = (3, 4);
+
+    let result = html! {
+        <span :let={(x, y) = point} :let={sum = x + y}>"{x} + {y} = {sum}"</span>
+    };
+
+    assert_eq!(result.to_string(), "<span>3 + 4 = 7</span>");
+}
+
+#[test]
+fn test_let_in_for() {
+    let items = vec![("apple", 3), ("pear", 2)];
+
+    let result = html! {
+        <ul>
+            <li :for={(name, count) in &items} :let={label = name.to_uppercase()}>"{label}: {count}"</li>
+        </ul>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        "<ul><li>APPLE: 3</li><li>PEAR: 2</li></ul>"
+    );
+}
+
+#[test]
+fn test_let_with_if() {
+    let items: Vec<i32> = vec![1, 2, 3];
+
+    let result = html! {
+        <p :if={!items.is_empty()} :let={total: i32 = items.iter().sum()}>"Total: {total}"</p>
+    };
+
+    assert_eq!(result.to_string(), "<p>Total: 6</p>");
+}
+
+#[test]
+fn test_let_in_component_and_fragment() {
+    let first = "Grace";
+
+    let result = html! {
+        <Greeting :let={name = format!("{first} Hopper")} name={name.clone()} />
+        <Fragment :let={count = 2}>
+            <span>{count}</span>
+            <span>{count * 2}</span>
+        </Fragment>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        "<p>Hello, Grace Hopper!</p><span>2</span><span>4</span>"
+    );
+}
@@ -0,0 +1,113 @@
+use hypersynthetic::prelude::*;
+
+enum State {
+    Loading,
+    Loaded(Vec<&'static str>),
+    Error { code: u16, message: String },
+}
+
+#[component]
+fn ErrorMessage(code: u16, message: &str) -> HtmlFragment {
+    html! {
+        <p class="error">"{code}: {message}"</p>
+    }
+}
+
+fn render(state: &State) -> String {
+    html! {
+        <div>
+            <template :match={state}>
+                <span :case={State::Loading}>"Loading..."</span>
+                <ul :case={State::Loaded(items)}>
+                    <li :for={item in items}>{item}</li>
+                </ul>
+                <ErrorMessage :case={State::Error { code, message }} code={*code} message={message} />
+            </template>
+        </div>
+    }
+    .to_string()
+}
+
+#[test]
+fn test_match() {
+    assert_eq!(
+        render(&State::Loading),
+        "<div><span>Loading...</span></div>"
+    );
+    assert_eq!(
+        render(&State::Loaded(vec!["a", "b"])),
+        "<div><ul><li>a</li><li>b</li></ul></div>"
+    );
+    assert_eq!(
+        render(&State::Error {
+            code: 404,
+            message: "Not found".to_string()
+        }),
+        "<div><p class=\"error\">404: Not found</p></div>"
+    );
+}
+
+#[test]
+fn test_match_with_guards_and_alternatives() {
+    let render = |n: Option<i32>| {
+        html! {
+            <template :match={n}>
+                <b :case={Some(x) if x < 0}>"negative {x}"</b>
+                <i :case={Some(0) | None}>"nothing"</i>
+                <span :case={Some(x)}>{x}</span>
+            </template>
+        }
+        .to_string()
+    };
+
+    assert_eq!(render(Some(-3)), "<b>negative -3</b>");
+    assert_eq!(render(Some(0)), "<i>nothing</i>");
+    assert_eq!(render(None), "<i>nothing</i>");
+    assert_eq!(render(Some(7)), "<span>7</span>");
+}
+
+#[test]
+fn test_case_with_for() {
+    let rows = Some(vec![1, 2]);
+
+    let result = html! {
+        <template :match={&rows}>
+            <p :case={Some(rows)} :for={row in rows}>{row}</p>
+            <p :case={None}>"No rows"</p>
+        </template>
+    };
+
+    assert_eq!(result.to_string(), "<p>1</p><p>2</p>");
+}
+
+#[test]
+fn test_template_without_match_is_an_element() {
+    let result = html! {
+        <template id="row"><tr></tr></template>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        "<template id=\"row\"><tr></tr></template>"
+    );
+}
+
+#[test]
+fn test_case_with_else_and_empty_siblings() {
+    let render = |rows: Option<Vec<i32>>, compact: bool| {
+        html! {
+            <template :match={&rows}>
+                <p :case={Some(rows)} :for={row in rows}>{row}</p>
+                <p :empty>"Empty"</p>
+                <b :case={None} :if={compact}>"-"</b>
+                <i :else>"No rows"</i>
+            </template>
+        }
+        .to_string()
+    };
+
+    assert_eq!(render(Some(vec![1, 2]), false), "<p>1</p><p>2</p>");
+    assert_eq!(render(Some(vec![]), false), "<p>Empty</p>");
+    assert_eq!(render(None, true), "<b>-</b>");
+    assert_eq!(render(None, false), "<i>No rows</i>");
+}
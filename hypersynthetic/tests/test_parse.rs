@@ -0,0 +1,190 @@
+use hypersynthetic::prelude::*;
+use hypersynthetic::{Node, parse::ParseError};
+
+fn roundtrip(html: &str) -> String {
+    HtmlFragment::parse(html).unwrap().to_string()
+}
+
+fn error(html: &str) -> (String, usize, usize) {
+    let ParseError {
+        message,
+        line,
+        column,
+    } = HtmlFragment::parse(html).unwrap_err();
+    (message, line, column)
+}
+
+#[test]
+fn test_parse_elements_and_text() {
+    let html = r#"<div class="card"><h1>Title</h1><p>Some <b>bold</b> text</p></div>"#;
+
+    assert_eq!(roundtrip(html), html);
+}
+
+#[test]
+fn test_parsed_tree_can_be_modified() {
+    let mut fragment = HtmlFragment::parse("<p>One</p>\n<p>Two</p>").unwrap();
+
+    for p in fragment.iter_elements_mut() {
+        p.set_attribute("class".to_owned(), "para".to_owned());
+    }
+
+    assert_eq!(
+        fragment.to_string(),
+        "<p class=\"para\">One</p>\n<p class=\"para\">Two</p>"
+    );
+    assert_eq!(fragment.iter().count(), 3);
+}
+
+#[test]
+fn test_parse_attributes() {
+    let fragment = HtmlFragment::parse(
+        r#"<INPUT Type=checkbox checked value='a "b"' data-x = "1" data-x="2">"#,
+    )
+    .unwrap();
+    let input = fragment.iter_elements().next().unwrap();
+
+    assert_eq!(input.tag_name, "input");
+    assert_eq!(input.get_attribute("type"), Some("checkbox".to_owned()));
+    assert_eq!(input.get_attribute("checked"), Some("".to_owned()));
+    assert_eq!(input.get_attribute("data-x"), Some("1".to_owned()));
+    assert_eq!(
+        fragment.to_string(),
+        r#"<input type="checkbox" checked value="a &quot;b&quot;" data-x="1" />"#
+    );
+}
+
+#[test]
+fn test_parse_void_and_self_closing_elements() {
+    assert_eq!(
+        roundtrip("<p>a<br>b<img src=x.png/></p><svg><path d=\"M0\"/></svg>"),
+        "<p>a<br />b<img src=\"x.png/\" /></p><svg><path d=\"M0\" /></svg>"
+    );
+}
+
+#[test]
+fn test_parse_ignores_self_closing_html_elements() {
+    assert_eq!(
+        roundtrip("<div/>text</div><span />x</span>"),
+        "<div>text</div><span>x</span>"
+    );
+    assert_eq!(
+        roundtrip("<svg><foreignObject><p/>a</p></foreignObject><g/></svg>"),
+        "<svg><foreignobject><p>a</p></foreignobject><g /></svg>"
+    );
+}
+
+#[test]
+fn test_parse_entities() {
+    assert_eq!(
+        roundtrip(
+            "<p title=\"&quot;x&quot; & y\">Fish &amp; Chips & 1 < 2 &nbsp;&#169;&#x2014;</p>"
+        ),
+        "<p title=\"&quot;x&quot; &amp; y\">Fish &amp; Chips &amp; 1 &lt; 2 &nbsp;&#169;&#x2014;</p>"
+    );
+}
+
+#[test]
+fn test_parse_keeps_references_without_semicolon() {
+    assert_eq!(
+        roundtrip("<p title=\"a&copy b\">&copy 2024 &amp &#169 & &; &#; &#xg</p>"),
+        "<p title=\"a&copy b\">&copy 2024 &amp &#169 &amp; &amp;; &amp;#; &amp;#xg</p>"
+    );
+}
+
+#[test]
+fn test_parse_raw_text_elements() {
+    let html =
+        "<script>if (a < b && c) { x('</div>') }</script><style>p > a { color: red }</style>";
+    assert_eq!(roundtrip(html), html);
+
+    assert_eq!(
+        roundtrip("<textarea><b>&amp; & </b></textarea><title>A & B</TITLE>"),
+        "<textarea>&lt;b&gt;&amp; &amp; &lt;/b&gt;</textarea><title>A &amp; B</title>"
+    );
+}
+
+#[test]
+fn test_parse_comments_and_doctype() {
+    let fragment = HtmlFragment::parse(
+        "<!doctype HTML><!-- a <b>comment</b> --><html><!----><!-- a -- b --><?xml?></html>",
+    )
+    .unwrap();
+
+    assert!(matches!(fragment.iter().next(), Some(Node::DocType)));
+    assert!(
+        matches!(fragment.iter().nth(1), Some(Node::Comment(text)) if text == " a <b>comment</b> ")
+    );
+    assert_eq!(
+        fragment.to_string(),
+        "<!DOCTYPE html><!-- a <b>comment</b> --><html><!----><!-- a - - b --></html>"
+    );
+}
+
+#[test]
+fn test_parse_optional_end_tags() {
+    assert_eq!(
+        roundtrip("<ul><li>a<li>b</ul>"),
+        "<ul><li>a</li><li>b</li></ul>"
+    );
+    assert_eq!(roundtrip("<p>one<p>two"), "<p>one</p><p>two</p>");
+    assert_eq!(
+        roundtrip("<p>text<div>block</div>"),
+        "<p>text</p><div>block</div>"
+    );
+    assert_eq!(
+        roundtrip("<dl><dt>term<dd>definition<dt>other</dl>"),
+        "<dl><dt>term</dt><dd>definition</dd><dt>other</dt></dl>"
+    );
+    assert_eq!(
+        roundtrip("<select><option>a<option selected>b</select>"),
+        "<select><option>a</option><option selected>b</option></select>"
+    );
+    assert_eq!(
+        roundtrip("<table><tr><th>a<td>b<tr><td>c</table>"),
+        "<table><tr><th>a</th><td>b</td></tr><tr><td>c</td></tr></table>"
+    );
+    assert_eq!(
+        roundtrip("<li>a<ul><li>b</ul><li>c"),
+        "<li>a<ul><li>b</li></ul></li><li>c</li>"
+    );
+}
+
+#[test]
+fn test_parse_errors_have_positions() {
+    assert_eq!(
+        error("<div>\n  <span>text</div>"),
+        ("expected `</span>`, found `</div>`".to_owned(), 2, 13)
+    );
+    assert_eq!(error("<ul>\n<li>"), ("unclosed `<ul>`".to_owned(), 1, 1));
+    assert_eq!(
+        error("<ul><li><b>a</ul>"),
+        ("expected `</b>`, found `</ul>`".to_owned(), 1, 13)
+    );
+    assert_eq!(error("text</p>"), ("unexpected `</p>`".to_owned(), 1, 5));
+    assert_eq!(
+        error("<br></br>"),
+        (
+            "`<br>` is a void element and can't have an end tag".to_owned(),
+            1,
+            5
+        )
+    );
+    assert_eq!(error("é <!-- x"), ("unclosed comment".to_owned(), 1, 3));
+    assert_eq!(
+        error("<a href=\"x>"),
+        ("unclosed `<a` tag".to_owned(), 1, 1)
+    );
+    assert_eq!(
+        error("<!DOCTYPE svg>"),
+        ("only `<!DOCTYPE html>` is supported".to_owned(), 1, 1)
+    );
+    assert_eq!(error("<script>x"), ("unclosed `<script>`".to_owned(), 1, 1));
+}
+
+#[test]
+fn test_parse_error_display() {
+    let error = "<div>".parse::<HtmlFragment>().unwrap_err();
+
+    assert_eq!(error.to_string(), "unclosed `<div>` at line 1, column 1");
+}
@@ -0,0 +1,144 @@
+use hypersynthetic::prelude::*;
+use hypersynthetic::pretty::PrettyOptions;
+
+fn pretty(fragment: &HtmlFragment) -> String {
+    fragment.to_pretty_string(&PrettyOptions::default())
+}
+
+#[test]
+fn test_pretty_indents_block_elements() {
+    let page = html! {
+        <!DOCTYPE html>
+        <html>
+            <head><title>"Home"</title></head>
+            <body>
+                <main>
+                    <h1>"Welcome"</h1>
+                    <p>"Read the "<a href="/docs">"docs"</a>"."</p>
+                    <hr />
+                </main>
+            </body>
+        </html>
+    };
+
+    assert_eq!(
+        pretty(&page),
+        "\
+<!DOCTYPE html>
+<html>
+  <head>
+    <title>Home</title>
+  </head>
+  <body>
+    <main>
+      <h1>Welcome</h1>
+      <p>Read the <a href=\"/docs\">docs</a>.</p>
+      <hr />
+    </main>
+  </body>
+</html>"
+    );
+}
+
+#[test]
+fn test_pretty_keeps_inline_content_together() {
+    let page = html! {
+        <div>
+            "Text "<em>"between"</em>
+            <p>"Paragraph"</p>
+            <span>"a"</span><span>"b"</span>
+        </div>
+    };
+
+    assert_eq!(
+        pretty(&page),
+        "\
+<div>
+  Text <em>between</em>
+  <p>Paragraph</p>
+  <span>a</span><span>b</span>
+</div>"
+    );
+}
+
+#[test]
+fn test_pretty_keeps_preformatted_content() {
+    let page = HtmlFragment::parse(
+        "<div><pre>  fn main() {\n      x\n  }</pre><textarea>a\n  b</textarea>\
+         <script>if (a < b) {\n  go();\n}</script></div>",
+    )
+    .unwrap();
+
+    assert_eq!(
+        pretty(&page),
+        "\
+<div>
+  <pre>  fn main() {
+      x
+  }</pre>
+  <textarea>a
+  b</textarea>
+  <script>if (a < b) {
+  go();
+}</script>
+</div>"
+    );
+}
+
+#[test]
+fn test_pretty_collapses_whitespace() {
+    let page =
+        HtmlFragment::parse("<ul>\n  <li>\n    One\n    <b> two </b>\n  </li>\n</ul>").unwrap();
+
+    assert_eq!(pretty(&page), "<ul>\n  <li>One <b> two </b></li>\n</ul>");
+}
+
+#[test]
+fn test_pretty_wraps_long_lines_at_whitespace() {
+    let page = html! {
+        <section>
+            <p>"The quick brown fox "<a href="/fox">"jumps over"</a>" the lazy dog."</p>
+        </section>
+    };
+    let options = PrettyOptions {
+        indent: "\t".to_owned(),
+        line_width: 24,
+    };
+
+    assert_eq!(
+        page.to_pretty_string(&options),
+        "\
+<section>
+\t<p>
+\t\tThe quick brown fox
+\t\t<a href=\"/fox\">jumps over</a>
+\t\tthe lazy dog.
+\t</p>
+</section>"
+    );
+}
+
+#[test]
+fn test_pretty_renders_the_same_page() {
+    let page = HtmlFragment::parse(
+        "<article class=\"post\"><h2>Title</h2><p>One <i>two</i> three</p>\
+         <custom-element>stays inline</custom-element></article>",
+    )
+    .unwrap();
+    let pretty = pretty(&page);
+
+    assert_eq!(
+        pretty,
+        "\
+<article class=\"post\">
+  <h2>Title</h2>
+  <p>One <i>two</i> three</p>
+  <custom-element>stays inline</custom-element>
+</article>"
+    );
+    let reparsed = HtmlFragment::parse(&pretty).unwrap();
+    assert_eq!(
+        reparsed.select("p").next().unwrap().children.to_string(),
+        "One <i>two</i> three"
+    );
+}
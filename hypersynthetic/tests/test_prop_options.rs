@@ -0,0 +1,80 @@
+use hypersynthetic::prelude::*;
+
+#[derive(Default)]
+enum Variant {
+    #[default]
+    Primary,
+    Danger,
+}
+
+impl std::fmt::Display for Variant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Variant::Primary => write!(f, "primary"),
+            Variant::Danger => write!(f, "danger"),
+        }
+    }
+}
+
+#[component]
+fn Button(
+    label: &str,
+    #[prop(default)] variant: Variant,
+    #[prop(default = false)] disabled: bool,
+    #[prop(default = "md".to_string(), into)] size: String,
+    #[prop(optional)] title: Option<&str>,
+) -> HtmlFragment {
+    html! {
+        <button class="btn-{variant} btn-{size}" title={title.unwrap_or(label)} :if={!disabled}>
+            {label}
+        </button>
+        <span :else>{label}</span>
+    }
+}
+
+#[test]
+fn test_defaults() {
+    let result = html! {
+        <Button label="Save" />
+    };
+
+    assert_eq!(
+        result.to_string(),
+        "<button class=\"btn-primary btn-md\" title=\"Save\">Save</button>"
+    );
+}
+
+#[test]
+fn test_overriding_defaults() {
+    let result = html! {
+        <Button label="Delete" variant={Variant::Danger} size="lg" title="Delete forever" />
+        <Button label="Off" disabled={true} />
+    };
+
+    assert_eq!(
+        result.to_string(),
+        "<button class=\"btn-danger btn-lg\" title=\"Delete forever\">Delete</button><span>Off</span>"
+    );
+}
+
+#[component]
+fn Greeting(#[prop(into)] name: String, inner: HtmlFragment) -> HtmlFragment {
+    html! {
+        <p>"Hello, {name}!"</p>
+        {{ inner }}
+    }
+}
+
+#[test]
+fn test_into() {
+    let owned = String::from("owned");
+    let result = html! {
+        <Greeting name="borrowed" />
+        <Greeting name={owned} />
+    };
+
+    assert_eq!(
+        result.to_string(),
+        "<p>Hello, borrowed!</p><p>Hello, owned!</p>"
+    );
+}
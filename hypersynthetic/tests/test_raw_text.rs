@@ -0,0 +1,161 @@
+use hypersynthetic::escape::Trusted;
+use hypersynthetic::prelude::*;
+
+#[test]
+fn test_script_is_not_escaped() {
+    let result = html! {
+        <script>
+            if (a < b && c > d) {
+                console.log("a & b");
+            }
+        </script>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        "<script>if (a < b && c > d) {\nconsole.log(\"a & b\");\n}</script>"
+    );
+}
+
+#[test]
+fn test_script_keeps_line_breaks() {
+    let result = html! {
+        <script>
+            let count = 1
+            count += 1
+        </script>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        "<script>let count = 1\ncount += 1</script>"
+    );
+}
+
+#[test]
+fn test_script_interpolation_is_js_escaped() {
+    let name = "</script><script>alert('x')";
+    let result = html! {
+        <script>const name = "Hello, {name}!";</script>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        r#"<script>const name = "Hello, \u003C/script\u003E\u003Cscript\u003Ealert(\'x\')!";</script>"#
+    );
+}
+
+#[test]
+fn test_script_with_attributes_and_braces() {
+    let endpoint = "/items";
+    let result = html! {
+        <script type="module">
+            htmx.on("htmx:load", (event) => { fetch("{endpoint}", {method: "POST"}) });
+            const empty = "{{}}";
+        </script>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        "<script type=\"module\">htmx.on(\"htmx:load\", (event) => { fetch(\"/items\", {method: \"POST\"}) });\nconst empty = \"{}\";</script>"
+    );
+}
+
+#[test]
+fn test_script_from_string_literal() {
+    let message = "it's";
+    let result = html! {
+        <script>"alert('{message}' + `<b>`);"</script>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        r"<script>alert('it\'s' + `<b>`);</script>"
+    );
+}
+
+#[test]
+fn test_script_value_interpolation() {
+    let name = "</script>";
+    let config = r#"{"debug": true}"#;
+    let result = html! {
+        <script>
+            const name = {{name}};
+            const config = {{Trusted(config)}};
+        </script>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        "<script>const name = \"\\u003C/script\\u003E\";\nconst config = {\"debug\": true};</script>"
+    );
+}
+
+#[test]
+fn test_json_script() {
+    let data = "it's <b>";
+    let result = html! {
+        <script type="application/json">{{ data }}</script>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        r#"<script type="application/json">"it\u0027s \u003Cb\u003E"</script>"#
+    );
+}
+
+#[test]
+fn test_string_literal_interpolation_outside_quotes() {
+    let (name, count) = ("Bob\"", 3);
+    let result = html! {
+        <script>"const user = {{ name: {name}, greeting: 'Hi {name}', count: {count} }};"</script>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        r#"<script>const user = { name: "Bob\"", greeting: 'Hi Bob\"', count: "3" };</script>"#
+    );
+}
+
+#[test]
+fn test_style_value_interpolation() {
+    let (color, width) = ("red; background: url(x)", "2px");
+    let result = html! {
+        <style>
+            p { color: {{color}}; width: {{width}}; }
+        </style>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        r"<style>p { color: red\3B  background\3A  url\28 x\29 ; width: 2px; }</style>"
+    );
+}
+
+#[test]
+fn test_style() {
+    let font = "Fira \"Sans\"";
+    let result = html! {
+        <style>
+            .card > p:hover {
+                color: #333;
+                margin: 0 1.5rem;
+                font-family: "{font}", sans-serif;
+            }
+        </style>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        "<style>.card > p:hover {\ncolor: #333;\nmargin: 0 1.5rem;\nfont-family: \"Fira \\22 Sans\\22 \", sans-serif;\n}</style>"
+    );
+}
+
+#[test]
+fn test_empty_script() {
+    let result = html! {
+        <script src="/app.js"></script>
+    };
+
+    assert_eq!(result.to_string(), r#"<script src="/app.js"></script>"#);
+}
@@ -0,0 +1,90 @@
+use hypersynthetic::prelude::*;
+
+fn page() -> HtmlFragment {
+    let rows = [("Alice", 31), ("Bob", 27)];
+    html! {
+        <!DOCTYPE html>
+        <table class="people">
+            <tr :for={(name, age) in rows}>
+                <td>{name}</td>
+                <td>{age}</td>
+            </tr>
+        </table>
+        <input type="checkbox" checked />
+    }
+}
+
+const EXPECTED: &str = "<!DOCTYPE html>\
+    <table class=\"people\">\
+        <tr><td>Alice</td><td>31</td></tr>\
+        <tr><td>Bob</td><td>27</td></tr>\
+    </table>\
+    <input type=\"checkbox\" checked />";
+
+#[test]
+fn test_render_to_string_buffer() {
+    let mut buffer = String::from("prefix:");
+    page().render_to(&mut buffer).unwrap();
+
+    assert_eq!(buffer, format!("prefix:{EXPECTED}"));
+}
+
+#[test]
+fn test_write_to_io_sink() {
+    let mut buffer: Vec<u8> = Vec::new();
+    page().write_to(&mut buffer).unwrap();
+
+    assert_eq!(String::from_utf8(buffer).unwrap(), EXPECTED);
+}
+
+#[test]
+fn test_display_matches_render_to() {
+    let fragment = page();
+
+    assert_eq!(format!("{fragment}"), EXPECTED);
+    assert_eq!(fragment.to_string(), EXPECTED);
+}
+
+#[test]
+fn test_render_string_allocates_the_size_hint() {
+    let fragment = page();
+    let rendered = fragment.render_string();
+
+    assert_eq!(rendered, EXPECTED);
+    assert_eq!(rendered.capacity(), EXPECTED.len());
+    for node in &fragment {
+        assert_eq!(node.render_string(), node.to_string());
+    }
+}
+
+#[test]
+fn test_size_hint_is_exact() {
+    let fragment = page();
+
+    assert_eq!(fragment.size_hint(), EXPECTED.len());
+    for node in &fragment {
+        assert_eq!(node.size_hint(), node.to_string().len());
+    }
+}
+
+#[test]
+fn test_write_to_propagates_io_errors() {
+    struct FailingWriter;
+
+    impl std::io::Write for FailingWriter {
+        fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::new(
+                std::io::ErrorKind::BrokenPipe,
+                "closed",
+            ))
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let error = page().write_to(&mut FailingWriter).unwrap_err();
+
+    assert_eq!(error.kind(), std::io::ErrorKind::BrokenPipe);
+}
@@ -0,0 +1,151 @@
+use hypersynthetic::escape::Trusted;
+use hypersynthetic::prelude::*;
+
+#[component(rest_attrs)]
+fn Button(label: &str, attrs: Attributes) -> HtmlFragment {
+    html! {
+        <button type="button" {..attrs}>{label}</button>
+    }
+}
+
+#[test]
+fn test_rest_attributes_are_forwarded() {
+    let result = html! {
+        <Button class="x" hx-post="/save" label="Go" />
+    };
+
+    assert_eq!(
+        result.to_string(),
+        "<button type=\"button\" class=\"x\" hx-post=\"/save\">Go</button>"
+    );
+}
+
+#[test]
+fn test_rest_attributes_without_rest() {
+    let result = html! {
+        <Button label="Go" />
+    };
+
+    assert_eq!(result.to_string(), "<button type=\"button\">Go</button>");
+}
+
+#[test]
+fn test_rest_attributes_values() {
+    let id = 7;
+    let result = html! {
+        <Button label="Delete" data-id={id} title="Item {id}" disabled type="submit" />
+    };
+
+    assert_eq!(
+        result.to_string(),
+        "<button type=\"submit\" data-id=\"7\" title=\"Item 7\" disabled>Delete</button>"
+    );
+}
+
+#[component(rest_attrs)]
+fn Card(children: HtmlFragment, title: String, attrs: Attributes) -> HtmlFragment {
+    html! {
+        <section {..attrs}>
+            <h2>{title}</h2>
+            {{ children }}
+        </section>
+    }
+}
+
+#[test]
+fn test_rest_attributes_with_slot() {
+    let result = html! {
+        <Card id="intro" title={"Intro".to_string()}>
+            <p>"Hello"</p>
+        </Card>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        "<section id=\"intro\"><h2>Intro</h2><p>Hello</p></section>"
+    );
+}
+
+#[component(rest_attrs)]
+fn Link(href: &str, attrs: Attributes) -> HtmlFragment {
+    html! {
+        <a href={href} {..attrs}>{href}</a>
+    }
+}
+
+#[test]
+fn test_rest_attributes_with_lifetimes() {
+    let href = String::from("/home");
+    let result = html! {
+        <Link href={&href} rel="nofollow" />
+    };
+
+    assert_eq!(
+        result.to_string(),
+        "<a href=\"/home\" rel=\"nofollow\">/home</a>"
+    );
+}
+
+#[component(rest_attrs)]
+fn Badge(active: bool, attrs: Attributes) -> HtmlFragment {
+    html! {
+        <span class="badge" class:active={active} style="color: red" {..attrs}>"New"</span>
+    }
+}
+
+#[component(rest_attrs)]
+fn Tag(attrs: Attributes) -> HtmlFragment {
+    html! {
+        <span {..attrs} class="tag" class:tag={true}>"Tag"</span>
+    }
+}
+
+#[test]
+fn test_rest_class_and_style_are_merged() {
+    let result = html! {
+        <Badge active={true} class="big badge" style="margin: 0; color: blue" />
+        <Tag class="big" />
+    };
+
+    assert_eq!(
+        result.to_string(),
+        "<span class=\"badge active big\" style=\"color: blue; margin: 0;\">New</span>\
+         <span class=\"big tag\">Tag</span>"
+    );
+}
+
+#[test]
+fn test_rest_attributes_are_escaped_for_their_context() {
+    let (url, code) = ("javascript:alert(1)", "alert(1)");
+    let result = html! {
+        <Button label="A" formaction={url} onclick={code} hx-on:click={Trusted("go()")} />
+        <Button label="B" onclick={Trusted(code)} />
+    };
+
+    assert_eq!(
+        result.to_string(),
+        "<button type=\"button\" formaction=\"about:invalid\" onclick=\"&quot;alert(1)&quot;\" \
+         hx-on:click=\"go()\">A</button><button type=\"button\" onclick=\"alert(1)\">B</button>"
+    );
+}
+
+#[component(rest_attrs)]
+fn Widget(name: &str, extra: Attributes) -> HtmlFragment {
+    html! {
+        <span {..extra}>{name}</span>
+    }
+}
+
+#[test]
+fn test_any_attribute_that_is_not_a_prop_is_a_rest_attribute() {
+    let result = html! {
+        <Card title={"Outer".to_string()} foo="1">
+            <Widget name="w" foo="2" frobnicate={3} />
+        </Card>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        "<section foo=\"1\"><h2>Outer</h2><span foo=\"2\" frobnicate=\"3\">w</span></section>"
+    );
+}
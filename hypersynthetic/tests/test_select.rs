@@ -0,0 +1,234 @@
+use hypersynthetic::prelude::*;
+
+fn page() -> HtmlFragment {
+    HtmlFragment::parse(
+        r#"<div id="main" class="content wide">
+            <h1 lang="en-US">Title</h1>
+            <p class="intro">Intro <a href="https://example.com">out</a></p>
+            <p>Body <a href="/docs?a=1&amp;b=2" data-kind="Internal Link">in</a></p>
+            <ul><li>1</li><li>2</li><li>3</li><li>4</li><li>5</li></ul>
+        </div>
+        <p class="footer">Footer</p>"#,
+    )
+    .unwrap()
+}
+
+fn select(fragment: &HtmlFragment, selector: &str) -> Vec<String> {
+    fragment
+        .select(selector)
+        .map(|element| element.to_string())
+        .collect()
+}
+
+fn tags(fragment: &HtmlFragment, selector: &str) -> Vec<String> {
+    fragment
+        .select(selector)
+        .map(|element| element.tag_name.clone())
+        .collect()
+}
+
+#[test]
+fn test_select_type_class_and_id() {
+    let page = page();
+
+    assert_eq!(tags(&page, "p").len(), 3);
+    assert_eq!(
+        select(&page, "p.intro a"),
+        [r#"<a href="https://example.com">out</a>"#]
+    );
+    assert_eq!(tags(&page, "#main.wide"), ["div"]);
+    assert_eq!(tags(&page, ".content.narrow"), Vec::<String>::new());
+    assert_eq!(tags(&page, "H1"), ["h1"]);
+    assert_eq!(tags(&page, "*").len(), 13);
+}
+
+#[test]
+fn test_select_attributes() {
+    let page = page();
+
+    assert_eq!(
+        select(&page, "a[href^=http]"),
+        [r#"<a href="https://example.com">out</a>"#]
+    );
+    assert_eq!(tags(&page, "[href$='b=2']"), ["a"]);
+    assert_eq!(tags(&page, r#"a[href*="a=1&b"]"#), ["a"]);
+    assert_eq!(tags(&page, "[data-kind~=Link]"), ["a"]);
+    assert_eq!(tags(&page, "[data-kind~=link]").len(), 0);
+    assert_eq!(tags(&page, "[data-kind='internal link' i]"), ["a"]);
+    assert_eq!(tags(&page, "[lang|=en]"), ["h1"]);
+    assert_eq!(tags(&page, "[class]").len(), 3);
+}
+
+#[test]
+fn test_select_combinators() {
+    let page = page();
+
+    assert_eq!(tags(&page, "div > a").len(), 0);
+    assert_eq!(tags(&page, "div a").len(), 2);
+    assert_eq!(tags(&page, "h1 + p"), ["p"]);
+    assert_eq!(select(&page, "h1 ~ p > a").len(), 2);
+    assert_eq!(
+        select(&page, "div ~ p"),
+        [r#"<p class="footer">Footer</p>"#]
+    );
+    assert_eq!(tags(&page, "h1, .footer, h1"), ["h1", "p"]);
+}
+
+#[test]
+fn test_select_pseudo_classes() {
+    let page = page();
+    let items = |selector: &str| -> Vec<String> {
+        page.select(selector)
+            .map(|li| li.children.to_string())
+            .collect()
+    };
+
+    assert_eq!(items("li:first-child"), ["1"]);
+    assert_eq!(items("li:last-child"), ["5"]);
+    assert_eq!(items("li:nth-child(odd)"), ["1", "3", "5"]);
+    assert_eq!(items("li:nth-child(2n)"), ["2", "4"]);
+    assert_eq!(items("li:nth-child(-n + 2)"), ["1", "2"]);
+    assert_eq!(items("li:nth-child(3)"), ["3"]);
+    assert_eq!(items("li:nth-last-child(2)"), ["4"]);
+    assert_eq!(items("li:not(:first-child, :nth-child(n+4))"), ["2", "3"]);
+    assert_eq!(tags(&page, "a:only-child"), ["a", "a"]);
+    assert_eq!(tags(&page, "p:not(.intro):not(.footer)"), ["p"]);
+}
+
+#[test]
+fn test_select_nested_matches() {
+    let fragment = HtmlFragment::parse("<div><div><div></div></div></div>").unwrap();
+
+    assert_eq!(tags(&fragment, "div").len(), 3);
+    assert_eq!(tags(&fragment, "div div").len(), 2);
+    assert_eq!(tags(&fragment, "div > div > div").len(), 1);
+}
+
+#[test]
+fn test_select_mut() {
+    let mut page = page();
+
+    for mut a in page.select_mut("a:not([href^='/'])") {
+        a.set_attribute("rel".to_owned(), "noopener".to_owned());
+    }
+    for div in page.select_mut("div") {
+        *div.tag_name = "section".to_owned();
+    }
+
+    assert_eq!(
+        select(&page, "[rel]"),
+        [r#"<a href="https://example.com" rel="noopener">out</a>"#]
+    );
+    assert_eq!(tags(&page, "#main"), ["section"]);
+}
+
+#[test]
+fn test_select_mut_attributes() {
+    let mut fragment =
+        HtmlFragment::parse(r#"<a HREF="/a?x=1&amp;y=2" title="old" id="a">A</a>"#).unwrap();
+
+    for mut a in fragment.select_mut("a") {
+        assert!(a.has_attribute("href"));
+        assert_eq!(a.get_attribute("Href").as_deref(), Some("/a?x=1&y=2"));
+        a.set_attribute("TITLE".to_owned(), "\"new\"".to_owned());
+        a.remove_attribute("ID");
+    }
+
+    assert_eq!(
+        fragment.to_string(),
+        r#"<a href="/a?x=1&amp;y=2" title="&quot;new&quot;">A</a>"#
+    );
+}
+
+#[test]
+fn test_select_mut_nested_matches() {
+    let mut fragment = HtmlFragment::parse("<ul><li><ul><li></li></ul></li></ul>").unwrap();
+
+    for (i, mut ul) in fragment.select_mut("ul").enumerate() {
+        ul.set_attribute("data-level".to_owned(), i.to_string());
+    }
+
+    assert_eq!(
+        fragment.to_string(),
+        r#"<ul data-level="0"><li><ul data-level="1"><li></li></ul></li></ul>"#
+    );
+}
+
+#[component]
+fn Card(children: HtmlFragment) -> HtmlFragment {
+    html! {
+        <div class="card">{{ children }}</div>
+    }
+}
+
+#[test]
+fn test_select_in_macro_output() {
+    let page = html! {
+        <main>
+            <Card><a href="https://example.com">"out"</a></Card>
+        </main>
+    };
+
+    assert_eq!(tags(&page, "main .card > a"), ["a"]);
+    assert_eq!(
+        page.to_string(),
+        r#"<main><div class="card"><a href="https://example.com">out</a></div></main>"#
+    );
+}
+
+#[test]
+fn test_select_in_static_markup() {
+    let active = "b";
+    let page = html! {
+        <ul>
+            <li>"a"</li>
+            <li><a href="/b">"b"</a></li>
+            <li class={active}>"c"</li>
+        </ul>
+    };
+
+    assert_eq!(tags(&page, "ul > li:nth-child(2) > a"), ["a"]);
+    assert_eq!(tags(&page, "li + li + li.b"), ["li"]);
+    assert_eq!(tags(&page, "li:last-child"), ["li"]);
+    // The first two items are rendered at compile time
+    let ul = page.iter_elements().next().unwrap();
+    assert_eq!(ul.children.iter_elements().count(), 1);
+}
+
+#[test]
+fn test_invalid_selectors() {
+    let page = page();
+    let error = |selector: &str| {
+        page.try_select(selector)
+            .err()
+            .map(|error| error.to_string())
+    };
+
+    assert_eq!(
+        error("div >"),
+        Some("expected a selector at column 6".to_owned())
+    );
+    assert_eq!(
+        error("a[href"),
+        Some("expected an attribute operator or `]` at column 7".to_owned())
+    );
+    assert_eq!(
+        error("li:hover"),
+        Some("unsupported pseudo-class `:hover` at column 4".to_owned())
+    );
+    assert_eq!(
+        error("li:nth-child(2x)"),
+        Some("invalid `an+b` expression `2x` at column 14".to_owned())
+    );
+    assert_eq!(error("p)"), Some("unexpected `)` at column 2".to_owned()));
+    assert_eq!(
+        error("p,"),
+        Some("expected a selector at column 3".to_owned())
+    );
+}
+
+#[test]
+#[should_panic(expected = "invalid selector `[`")]
+fn test_select_panics_on_invalid_selector() {
+    page().select("[");
+}
@@ -76,3 +76,114 @@ fn test_slots_with_props() {
         "<div class=\"blue round\"><p>Hello, world!</p></div>"
     );
 }
+
+#[component]
+fn Card(body: HtmlFragment, header: HtmlFragment, footer: HtmlFragment) -> HtmlFragment {
+    html! {
+        <div class="card">
+            <header>{{ header }}</header>
+            <main>{{ body }}</main>
+            <footer>{{ footer }}</footer>
+        </div>
+    }
+}
+
+#[test]
+fn test_named_slots() {
+    let title = "Hello";
+    let result = html! {
+        <Card>
+            <:header><h1>{title}</h1></:header>
+            <:footer>"Bye"</:footer>
+            <p>"Body"</p>
+        </Card>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        "<div class=\"card\"><header><h1>Hello</h1></header><main><p>Body</p></main><footer>Bye</footer></div>"
+    );
+}
+
+#[test]
+fn test_slot_attribute() {
+    let result = html! {
+        <Card>
+            <h1 slot="header" class="title">"Title"</h1>
+            <p>"Body"</p>
+            <small slot="footer">"1"</small>
+            <small slot="footer">"2"</small>
+        </Card>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        "<div class=\"card\"><header><h1 class=\"title\">Title</h1></header><main><p>Body</p></main><footer><small>1</small><small>2</small></footer></div>"
+    );
+}
+
+#[test]
+fn test_unfilled_slots_are_empty() {
+    let result = html! {
+        <Card>
+            <:header>"Only header"</:header>
+        </Card>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        "<div class=\"card\"><header>Only header</header><main></main><footer></footer></div>"
+    );
+
+    let result = html! {
+        <OrangeDiv />
+    };
+
+    assert_eq!(result.to_string(), "<div class=\"orange round\"></div>");
+}
+
+#[component]
+fn Section(title: &str, actions: HtmlFragment) -> HtmlFragment {
+    html! {
+        <section>
+            <h2>{title}</h2>
+            {{ actions }}
+        </section>
+    }
+}
+
+#[test]
+fn test_named_slot_without_default_slot() {
+    let result = html! {
+        <Section title="Settings">
+            <:actions>
+                <button :for={action in ["Save", "Reset"]}>{action}</button>
+            </:actions>
+        </Section>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        "<section><h2>Settings</h2><button>Save</button><button>Reset</button></section>"
+    );
+
+    let result = html! {
+        <Section title="Empty" />
+    };
+
+    assert_eq!(result.to_string(), "<section><h2>Empty</h2></section>");
+}
+
+#[test]
+fn test_slot_attribute_outside_of_components_is_kept() {
+    let result = html! {
+        <div>
+            <span slot="title">"Title"</span>
+        </div>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        "<div><span slot=\"title\">Title</span></div>"
+    );
+}
@@ -0,0 +1,93 @@
+use std::collections::BTreeMap;
+
+use hypersynthetic::prelude::*;
+
+#[test]
+fn test_spread_pairs() {
+    let extra = vec![("data-id", "42"), ("title", "Hello")];
+
+    let result = html! {
+        <div class="card" {..extra}>"Card"</div>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        "<div class=\"card\" data-id=\"42\" title=\"Hello\">Card</div>"
+    );
+}
+
+#[test]
+fn test_spread_map() {
+    let mut extra = BTreeMap::new();
+    extra.insert("aria-label".to_string(), "Close".to_string());
+    extra.insert("role".to_string(), "button".to_string());
+
+    let result = html! {
+        <span {..&extra}></span>
+        <span {..extra}></span>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        "<span aria-label=\"Close\" role=\"button\"></span><span aria-label=\"Close\" role=\"button\"></span>"
+    );
+}
+
+#[test]
+fn test_spread_attribute_set() {
+    let mut attrs = Attributes::new();
+    attrs.set("type", "checkbox");
+    attrs.set_flag("checked");
+
+    let result = html! {
+        <input {..&attrs} />
+        <input {..attrs} />
+    };
+
+    assert_eq!(
+        result.to_string(),
+        "<input type=\"checkbox\" checked /><input type=\"checkbox\" checked />"
+    );
+}
+
+#[test]
+fn test_spread_overrides_earlier_attributes() {
+    let attrs: Attributes = [("id", "overridden"), ("lang", "en")].into_iter().collect();
+
+    let result = html! {
+        <p id="original" class="text" {..attrs} title="kept">"Text"</p>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        "<p id=\"overridden\" class=\"text\" lang=\"en\" title=\"kept\">Text</p>"
+    );
+}
+
+#[test]
+fn test_spread_values_are_escaped() {
+    let extra = [("title", "\"quoted\" & <tagged>")];
+
+    let result = html! {
+        <abbr {..extra}>"Q"</abbr>
+    };
+
+    assert_eq!(
+        result.to_string(),
+        "<abbr title=\"&quot;quoted&quot; &amp; &lt;tagged&gt;\">Q</abbr>"
+    );
+}
+
+#[test]
+fn test_attributes() {
+    let mut attrs = Attributes::new();
+    attrs.set("id", 1);
+    attrs.set_flag("hidden");
+    attrs.set("id", 2);
+
+    assert_eq!(attrs.len(), 2);
+    assert_eq!(attrs.get("id"), Some("2"));
+    assert_eq!(attrs.get("hidden"), Some(""));
+    assert!(attrs.remove("hidden").is_some());
+    assert!(!attrs.contains("hidden"));
+}
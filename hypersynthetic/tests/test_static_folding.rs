@@ -0,0 +1,120 @@
+use hypersynthetic::{Node, prelude::*};
+
+#[test]
+fn test_static_children_are_folded() {
+    let result = html! {
+        <div class="card">
+            <h2 id="title">"Fish & Chips"</h2>
+            <p>"Served <hot>"</p>
+            <input type="checkbox" checked />
+        </div>
+    };
+
+    let div = result.iter_elements().next().unwrap();
+
+    assert_eq!(div.children.iter().count(), 1);
+    assert_eq!(div.children.iter_elements().count(), 0);
+    assert_eq!(
+        result.to_string(),
+        "<div class=\"card\"><h2 id=\"title\">Fish &amp; Chips</h2><p>Served &lt;hot&gt;</p><input type=\"checkbox\" checked /></div>"
+    );
+}
+
+#[test]
+fn test_children_around_expressions_are_folded_separately() {
+    let name = "<Ferris>";
+    let result = html! {
+        <div>
+            <span>"Hello"</span>
+            <i>"dear"</i>
+            <b>{name}</b>
+            "!"
+        </div>
+    };
+
+    let div = result.iter_elements().next().unwrap();
+    let tags: Vec<&str> = div
+        .children
+        .iter_elements()
+        .map(|element| element.tag_name.as_str())
+        .collect();
+
+    assert_eq!(div.children.iter().count(), 3);
+    assert_eq!(tags, ["b"]);
+    assert_eq!(
+        result.to_string(),
+        "<div><span>Hello</span><i>dear</i><b>&lt;Ferris&gt;</b>!</div>"
+    );
+}
+
+#[test]
+fn test_text_without_elements_is_not_folded() {
+    let result = html! {
+        <p>"Hi "<!-- "greeting" --></p>
+    };
+
+    let p = result.iter_elements().next().unwrap();
+    assert!(matches!(p.children.iter().next(), Some(Node::Text(text)) if text == "Hi "));
+    assert_eq!(result.to_string(), "<p>Hi <!-- greeting --></p>");
+}
+
+#[test]
+fn test_top_level_elements_are_not_folded() {
+    let result = html! {
+        <ul>
+            <li>"One"</li>
+        </ul>
+    };
+
+    let ul = result.iter_elements().next().unwrap();
+    assert_eq!(ul.tag_name, "ul");
+    assert_eq!(result.to_string(), "<ul><li>One</li></ul>");
+}
+
+#[test]
+fn test_static_markup_can_be_expanded() {
+    let name = "<Ferris>";
+    let mut result = html! {
+        <div>
+            <span>"Hello"</span>
+            <b>{name}</b>
+        </div>
+    };
+    result.expand_markup();
+
+    let div = result.iter_elements().next().unwrap();
+    let tags: Vec<&str> = div
+        .children
+        .iter_elements()
+        .map(|element| element.tag_name.as_str())
+        .collect();
+
+    assert_eq!(tags, ["span", "b"]);
+    assert_eq!(
+        result.to_string(),
+        "<div><span>Hello</span><b>&lt;Ferris&gt;</b></div>"
+    );
+}
+
+#[test]
+fn test_static_markup_keeps_the_case_of_names() {
+    let mut result = html! {
+        <svg viewBox="0 0 10 10"><foreignObject><p>"Hi"</p></foreignObject></svg>
+    };
+    result.expand_markup();
+
+    let svg = result.iter_elements().next().unwrap();
+    let foreign_object = svg.children.iter_elements().next().unwrap();
+    assert_eq!(foreign_object.tag_name, "foreignObject");
+    assert_eq!(svg.attributes[0].name, "viewBox");
+}
+
+#[test]
+fn test_text_with_format_braces_is_not_folded() {
+    let greeting = "Hi";
+    let result = html! {
+        <p>"{greeting}, {{friend}}"</p>
+    };
+
+    assert_eq!(result.to_string(), "<p>Hi, {friend}</p>");
+}
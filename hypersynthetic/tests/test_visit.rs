@@ -0,0 +1,221 @@
+use hypersynthetic::prelude::*;
+use hypersynthetic::visit::{Visitor, VisitorMut};
+use hypersynthetic::{ElementData, Node};
+
+fn page() -> HtmlFragment {
+    HtmlFragment::parse(
+        "<!DOCTYPE html><div><p>One <b>two</b></p><img src=\"a.png\"></div><span>three</span>",
+    )
+    .unwrap()
+}
+
+fn describe(node: &Node) -> String {
+    match node {
+        Node::Element(element) => element.tag_name.clone(),
+        Node::Text(text) => format!("{text:?}"),
+        Node::DocType => "doctype".to_owned(),
+        Node::Comment(text) => format!("comment {text:?}"),
+        node => format!("{node:?}"),
+    }
+}
+
+#[test]
+fn test_iter_depth_first() {
+    let page = page();
+    let nodes: Vec<(String, Option<String>, usize)> = page
+        .iter_depth_first()
+        .map(|item| {
+            (
+                describe(item.node),
+                item.parent.map(|parent| parent.tag_name.clone()),
+                item.depth,
+            )
+        })
+        .collect();
+
+    assert_eq!(
+        nodes,
+        [
+            ("doctype".to_owned(), None, 0),
+            ("div".to_owned(), None, 0),
+            ("p".to_owned(), Some("div".to_owned()), 1),
+            ("\"One \"".to_owned(), Some("p".to_owned()), 2),
+            ("b".to_owned(), Some("p".to_owned()), 2),
+            ("\"two\"".to_owned(), Some("b".to_owned()), 3),
+            ("img".to_owned(), Some("div".to_owned()), 1),
+            ("span".to_owned(), None, 0),
+            ("\"three\"".to_owned(), Some("span".to_owned()), 1),
+        ]
+    );
+}
+
+#[test]
+fn test_iter_breadth_first() {
+    let page = page();
+    let nodes: Vec<(String, usize)> = page
+        .iter_breadth_first()
+        .map(|item| (describe(item.node), item.depth))
+        .collect();
+
+    assert_eq!(
+        nodes,
+        [
+            ("doctype".to_owned(), 0),
+            ("div".to_owned(), 0),
+            ("span".to_owned(), 0),
+            ("p".to_owned(), 1),
+            ("img".to_owned(), 1),
+            ("\"three\"".to_owned(), 1),
+            ("\"One \"".to_owned(), 2),
+            ("b".to_owned(), 2),
+            ("\"two\"".to_owned(), 3),
+        ]
+    );
+}
+
+#[test]
+fn test_iterators_expand_static_markup() {
+    let page = html! {
+        <div><p>"Hi"</p><span>{"there"}</span></div>
+    };
+
+    let nodes: Vec<(String, Option<String>, usize)> = page
+        .iter_depth_first()
+        .map(|item| {
+            (
+                describe(item.node),
+                item.parent.map(|parent| parent.tag_name.clone()),
+                item.depth,
+            )
+        })
+        .collect();
+    assert_eq!(
+        nodes,
+        [
+            ("div".to_owned(), None, 0),
+            ("p".to_owned(), Some("div".to_owned()), 1),
+            ("\"Hi\"".to_owned(), Some("p".to_owned()), 2),
+            ("span".to_owned(), Some("div".to_owned()), 1),
+            ("\"there\"".to_owned(), Some("span".to_owned()), 2),
+        ]
+    );
+
+    let nodes: Vec<(String, usize)> = page
+        .iter_breadth_first()
+        .map(|item| (describe(item.node), item.depth))
+        .collect();
+    assert_eq!(
+        nodes,
+        [
+            ("div".to_owned(), 0),
+            ("p".to_owned(), 1),
+            ("span".to_owned(), 1),
+            ("\"Hi\"".to_owned(), 2),
+            ("\"there\"".to_owned(), 2),
+        ]
+    );
+}
+
+#[derive(Default)]
+struct Events(Vec<String>);
+
+impl Visitor for Events {
+    fn enter_element(&mut self, element: &ElementData) {
+        self.0.push(format!("<{}>", element.tag_name));
+    }
+
+    fn leave_element(&mut self, element: &ElementData) {
+        self.0.push(format!("</{}>", element.tag_name));
+    }
+
+    fn visit_text(&mut self, text: &str) {
+        self.0.push(text.to_owned());
+    }
+
+    fn visit_doctype(&mut self) {
+        self.0.push("doctype".to_owned());
+    }
+}
+
+#[test]
+fn test_visitor() {
+    let mut events = Events::default();
+    page().visit(&mut events);
+
+    assert_eq!(
+        events.0,
+        [
+            "doctype", "<div>", "<p>", "One ", "<b>", "two", "</b>", "</p>", "<img>", "</img>",
+            "</div>", "<span>", "three", "</span>"
+        ]
+    );
+
+    let mut events = Events::default();
+    html! { <p><br :static /></p> }.visit(&mut events);
+
+    assert_eq!(events.0, ["<p>", "<br>", "</br>", "</p>"]);
+}
+
+struct RewriteAssets;
+
+impl VisitorMut for RewriteAssets {
+    fn enter_element(&mut self, element: &mut ElementData) {
+        if let Some(src) = element.get_attribute("src") {
+            element.remove_attribute("src");
+            element.set_attribute("src".to_owned(), format!("https://cdn.example.com/{src}"));
+        }
+    }
+
+    fn visit_text(&mut self, text: &mut String) {
+        *text = text.to_uppercase();
+    }
+}
+
+#[test]
+fn test_visitor_mut() {
+    let mut page = page();
+    page.visit_mut(&mut RewriteAssets);
+
+    assert_eq!(
+        page.to_string(),
+        r#"<!DOCTYPE html><div><p>ONE <b>TWO</b></p><img src="https://cdn.example.com/a.png" /></div><span>THREE</span>"#
+    );
+}
+
+#[component]
+fn Figure(children: HtmlFragment) -> HtmlFragment {
+    html! {
+        <figure>{{ children }}</figure>
+    }
+}
+
+struct Wrap;
+
+impl VisitorMut for Wrap {
+    fn enter_element(&mut self, element: &mut ElementData) {
+        if element.tag_name == "figure" {
+            let mut caption = ElementData::new("figcaption".to_owned());
+            caption.add_child(Node::Text("caption".to_owned()));
+            element.add_child(Node::Element(caption));
+        }
+    }
+
+    fn visit_text(&mut self, text: &mut String) {
+        text.push('!');
+    }
+}
+
+#[test]
+fn test_visitor_mut_expands_markup_and_visits_added_children() {
+    let mut page = html! {
+        <main>
+            <Figure><img src="a.png" /></Figure>
+        </main>
+    };
+    page.visit_mut(&mut Wrap);
+
+    assert_eq!(
+        page.to_string(),
+        r#"<main><figure><img src="a.png" /><figcaption>caption!</figcaption></figure></main>"#
+    );
+}
@@ -1,11 +1,32 @@
-use syn::{Expr, LitStr, Pat};
+use syn::{Expr, Ident, LitStr, Pat, Token};
+
+use crate::nodes::Node;
 
 #[derive(Clone)]
 pub enum Attribute {
     #[allow(clippy::enum_variant_names)]
     RegularAttribute(RegularAttribute),
     For(ForExpr),
+    Let(LetBinding),
     If(Expr),
+    ElseIf(Expr),
+    Else(Token![else]),
+    Match(Expr),
+    Case(CaseExpr),
+    Class(ClassDirective),
+    Style(StyleDirective),
+    /// `{..expr}`
+    Spread(Expr),
+    Empty(Ident),
+}
+
+impl Attribute {
+    pub fn is_conditional(&self) -> bool {
+        matches!(
+            self,
+            Attribute::If(_) | Attribute::ElseIf(_) | Attribute::Else(_)
+        )
+    }
 }
 
 #[derive(Clone)]
@@ -15,26 +36,92 @@ pub struct RegularAttribute {
 }
 
 #[derive(Clone)]
+#[allow(clippy::large_enum_variant)]
 pub enum AttrName {
     Literal(LitStr),
     Expression(Expr),
 }
 
 #[derive(Clone)]
+#[allow(clippy::large_enum_variant)]
 pub enum AttrValue {
     Literal(LitStr),
     Expression(Expr),
     Interpolated(Vec<InterpolatedSegment>),
+    /// `name:if={condition}`: the attribute is rendered without a value when `condition` is true.
+    Toggle(Expr),
 }
 
 #[derive(Clone)]
+#[allow(clippy::large_enum_variant)]
 pub enum InterpolatedSegment {
     Str(LitStr),
     Expr(Expr),
 }
 
+/// `pat in collection`, or `pat in collection, info` to bind a `LoopInfo` to `info`.
 #[derive(Clone)]
 pub struct ForExpr {
     pub pat: Pat,
     pub collection: Expr,
+    pub info: Option<Ident>,
+    /// The `:empty` sibling, rendered when the collection yields nothing.
+    pub empty: Option<Box<Node>>,
+}
+
+/// `:let={pat = expr}`
+#[derive(Clone)]
+pub struct LetBinding {
+    pub pat: Pat,
+    pub expr: Expr,
+}
+
+/// `class:name={condition}`
+#[derive(Clone)]
+pub struct ClassDirective {
+    pub name: LitStr,
+    pub condition: Expr,
 }
+
+/// `style:property=value`
+#[derive(Clone)]
+pub struct StyleDirective {
+    pub property: LitStr,
+    pub value: AttrValue,
+}
+
+#[derive(Clone)]
+pub struct CaseExpr {
+    pub pat: Pat,
+    pub guard: Option<Expr>,
+}
+
+/// Boolean attributes from the HTML standard. Their presence alone means `true`,
+/// so `name={expr}` is rendered as `name` or omitted, depending on `expr`.
+/// `hidden` isn't one of them, because it can also be `until-found`.
+pub const BOOLEAN_ATTRIBUTES: &[&str] = &[
+    "allowfullscreen",
+    "async",
+    "autofocus",
+    "autoplay",
+    "checked",
+    "controls",
+    "default",
+    "defer",
+    "disabled",
+    "formnovalidate",
+    "inert",
+    "ismap",
+    "itemscope",
+    "loop",
+    "multiple",
+    "muted",
+    "nomodule",
+    "novalidate",
+    "open",
+    "playsinline",
+    "readonly",
+    "required",
+    "reversed",
+    "selected",
+];
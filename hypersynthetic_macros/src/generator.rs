@@ -1,14 +1,142 @@
 use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
+use syn::spanned::Spanned;
 
 use crate::{
-    attributes::{AttrName, AttrValue, InterpolatedSegment, RegularAttribute},
-    nodes::{Node, NodeCollection},
+    attributes::{
+        AttrName, AttrValue, Attribute, BOOLEAN_ATTRIBUTES, ClassDirective, ForExpr,
+        InterpolatedSegment, LetBinding, RegularAttribute, StyleDirective,
+    },
+    nodes::{Node, NodeCollection, RawSegment, RawTextLanguage},
 };
 
+/// How nodes and components are generated, which depends on the macro and the nodes around them.
+#[derive(Clone, Copy, Default)]
+struct Mode {
+    /// In `html_async!`, where components may return futures
+    is_async: bool,
+    /// How many `try_html!` and `<ErrorBoundary>` blocks are around, where components may
+    /// return a `Result` and the first error stops rendering the innermost block
+    try_depth: usize,
+    /// In the nodes of markup rendered at compile time, which are not folded again
+    is_static: bool,
+}
+
 pub fn generate_nodes(NodeCollection::Nodes(nodes): NodeCollection) -> TokenStream2 {
-    let nodes: Vec<TokenStream2> = nodes.into_iter().map(generate_node).collect();
+    generate_children(nodes, Mode::default())
+}
+
+/// Renders into a `Result`, returning the first error of a component.
+pub fn generate_try_nodes(NodeCollection::Nodes(nodes): NodeCollection) -> TokenStream2 {
+    let mode = Mode {
+        try_depth: 1,
+        ..Mode::default()
+    };
+    let fragment = generate_children(nodes, mode);
+    let label = try_label(mode);
+
+    quote! {
+        #label: {
+            ::std::result::Result::Ok(#fragment)
+        }
+    }
+}
 
+/// The block a failing component breaks out of with its error. A labeled block rather
+/// than a closure keeps `return` and `?` in the markup returning from the enclosing
+/// function. The label is hygienic, so it can't clash with the labels of the caller.
+fn try_label(mode: Mode) -> syn::Lifetime {
+    syn::Lifetime::new(
+        &format!("'hypersynthetic_try{}", mode.try_depth),
+        proc_macro2::Span::mixed_site(),
+    )
+}
+
+/// The tree is built right away, with a placeholder for every async component.
+/// The returned future renders them and fills the placeholders in.
+pub fn generate_async_nodes(NodeCollection::Nodes(nodes): NodeCollection) -> TokenStream2 {
+    let mode = Mode {
+        is_async: true,
+        ..Mode::default()
+    };
+    let fragment = generate_children(nodes, mode);
+
+    quote! {
+        {
+            let mut pending_components = hypersynthetic::async_render::Pending::new();
+            let fragment = #fragment;
+            pending_components.resolve(fragment)
+        }
+    }
+}
+
+fn generate_children(children: Vec<Node>, mode: Mode) -> TokenStream2 {
+    wrap_nodes(
+        children
+            .into_iter()
+            .map(|node| generate_node(node, mode))
+            .collect(),
+    )
+}
+
+// Runs of children without expressions that contain an element are rendered at compile time
+// and emitted as a single `Node::Static`. Its element tree is built the first time it is
+// needed, by `select` or `visit` for example.
+fn generate_element_children(children: Vec<Node>, mode: Mode) -> TokenStream2 {
+    if mode.is_static {
+        return generate_children(children, mode);
+    }
+    let mut nodes = Vec::new();
+    let mut static_run = Vec::new();
+    let mut static_html_run = String::new();
+
+    for child in children {
+        if let Some(html) = static_html(&child) {
+            static_html_run.push_str(&html);
+            static_run.push(child);
+            continue;
+        }
+        flush_static_run(&mut nodes, &mut static_run, &mut static_html_run, mode);
+        nodes.push(generate_node(child, mode));
+    }
+    flush_static_run(&mut nodes, &mut static_run, &mut static_html_run, mode);
+
+    wrap_nodes(nodes)
+}
+
+fn flush_static_run(
+    nodes: &mut Vec<TokenStream2>,
+    static_run: &mut Vec<Node>,
+    static_html_run: &mut String,
+    mode: Mode,
+) {
+    let has_element = static_run
+        .iter()
+        .any(|node| matches!(node, Node::Element(_) | Node::Fragment(_)));
+    if has_element {
+        nodes.push(generate_static(static_html_run, std::mem::take(static_run)));
+    } else {
+        nodes.extend(static_run.drain(..).map(|node| generate_node(node, mode)));
+    }
+    static_html_run.clear();
+}
+
+fn generate_static(html: &str, run: Vec<Node>) -> TokenStream2 {
+    let mode = Mode {
+        is_static: true,
+        ..Mode::default()
+    };
+    let fragment = generate_children(run, mode);
+    quote! {
+        {
+            static MARKUP: hypersynthetic::StaticMarkup =
+                hypersynthetic::StaticMarkup::new(#html, || #fragment);
+            vec![hypersynthetic::Node::Static(&MARKUP)]
+        }
+    }
+}
+
+fn wrap_nodes(nodes: Vec<TokenStream2>) -> TokenStream2 {
     let nodes: Vec<TokenStream2> = nodes
         .into_iter()
         .map(|node| {
@@ -29,45 +157,41 @@ pub fn generate_nodes(NodeCollection::Nodes(nodes): NodeCollection) -> TokenStre
     }
 }
 
-fn generate_node(tag: Node) -> TokenStream2 {
+fn generate_node(tag: Node, mode: Mode) -> TokenStream2 {
     match tag {
         Node::Element(element) => {
             let tag_name = element.tag_name.to_string();
             let self_closing = element.self_closing;
-            let children: TokenStream2 =
-                generate_nodes(NodeCollection::Nodes(element.children.clone()));
-            let attributes: Vec<TokenStream2> = element
-                .get_regular_attributes()
-                .into_iter()
-                .map(generate_attribute)
-                .collect();
+            let children: TokenStream2 = generate_element_children(element.children.clone(), mode);
+            let attributes = generate_attributes(&element.attributes);
             let tokens = if element.has_for_attribute() {
-                let for_expr = element.get_for_attribute();
-                let var = for_expr.pat;
-                let collection = for_expr.collection;
-                quote! {
-                    {
-                        let mut for_v = Vec::new();
-                        for #var in #collection {
+                generate_for(
+                    element.get_for_attribute(),
+                    with_let_bindings(
+                        &element.attributes,
+                        quote! {
                             for_v.push(hypersynthetic::Node::Element(hypersynthetic::ElementData {
                                 tag_name: #tag_name.to_owned(),
-                                attributes: vec![#(#attributes),*],
+                                attributes: #attributes,
                                 children: #children,
                                 self_closing: #self_closing,
                             }));
-                        }
-                        for_v
-                    }
-                }
+                        },
+                    ),
+                    mode,
+                )
             } else {
-                quote! {
-                    vec![hypersynthetic::Node::Element(hypersynthetic::ElementData {
-                        tag_name: #tag_name.to_owned(),
-                        attributes: vec![#(#attributes),*],
-                        children: #children,
-                        self_closing: #self_closing,
-                    })]
-                }
+                with_let_bindings(
+                    &element.attributes,
+                    quote! {
+                        vec![hypersynthetic::Node::Element(hypersynthetic::ElementData {
+                            tag_name: #tag_name.to_owned(),
+                            attributes: #attributes,
+                            children: #children,
+                            self_closing: #self_closing,
+                        })]
+                    },
+                )
             };
 
             if element.has_if_attribute() {
@@ -88,6 +212,13 @@ fn generate_node(tag: Node) -> TokenStream2 {
                 vec![hypersynthetic::Node::Text(hypersynthetic::escape_text(format!(#text)).to_string())]
             }
         }
+        Node::BareText(text) => {
+            quote! {
+                vec![hypersynthetic::Node::Text(
+                    hypersynthetic::escape::escape_text_keeping_references(#text)
+                )]
+            }
+        }
         Node::Expression(expr) => {
             quote! {
                 vec![hypersynthetic::Node::Text(hypersynthetic::escape_text(format!("{}", #expr)).to_string())]
@@ -95,7 +226,10 @@ fn generate_node(tag: Node) -> TokenStream2 {
         }
         Node::UnescapedExpression(expr) => {
             quote! {
-                vec![hypersynthetic::Node::Text(format!("{}", #expr))]
+                {
+                    use hypersynthetic::escape::{DisplayNodes as _, FragmentNodes as _};
+                    (&hypersynthetic::escape::Unescaped(&#expr)).to_nodes()
+                }
             }
         }
         Node::DocType => {
@@ -103,30 +237,196 @@ fn generate_node(tag: Node) -> TokenStream2 {
                 vec![hypersynthetic::Node::DocType]
             }
         }
+        Node::Comment(segments) => {
+            let interpolated: Vec<TokenStream2> = segments
+                .iter()
+                .map(|segment| match segment {
+                    InterpolatedSegment::Str(s) => quote! { #s },
+                    InterpolatedSegment::Expr(e) => quote! { #e },
+                })
+                .collect();
+            let format_pattern = generate_format_string_pattern(interpolated.len());
+            quote! {
+                vec![hypersynthetic::Node::Comment(hypersynthetic::escape::escape_comment(
+                    &format!(#format_pattern, #(#interpolated),*)
+                ))]
+            }
+        }
+        Node::Fragment(fragment) => {
+            if let Some(fallback) = &fragment.fallback
+                && mode.is_async
+            {
+                return syn::Error::new(
+                    fallback.span(),
+                    "`<ErrorBoundary>` can't be used in `html_async!`",
+                )
+                .to_compile_error();
+            }
+
+            let children_mode = Mode {
+                try_depth: mode.try_depth + usize::from(fragment.fallback.is_some()),
+                ..mode
+            };
+            let children: Vec<TokenStream2> = fragment
+                .children
+                .iter()
+                .cloned()
+                .map(|node| generate_node(node, children_mode))
+                .collect();
+            // Async components may be polled on another thread
+            let provide_fn = if mode.is_async {
+                quote! { provide_shared }
+            } else {
+                quote! { provide }
+            };
+            let provide = fragment.context.as_ref().map(|value| {
+                quote! { let _provider_guard = hypersynthetic::context::#provide_fn(#value); }
+            });
+            let nodes = quote! {
+                {
+                    #provide
+                    let mut fragment_v = Vec::new();
+                    #(fragment_v.extend(#children);)*
+                    fragment_v
+                }
+            };
+            let nodes = match &fragment.fallback {
+                Some(fallback) => {
+                    let label = try_label(children_mode);
+                    quote! {
+                        {
+                            let result = #label: {
+                                ::std::result::Result::Ok(#nodes)
+                            };
+                            match result {
+                                ::std::result::Result::Ok(nodes) => nodes,
+                                ::std::result::Result::Err(error) => (#fallback)(error).get_nodes(),
+                            }
+                        }
+                    }
+                }
+                None => nodes,
+            };
+            let nodes = with_let_bindings(&fragment.attributes, nodes);
+
+            let tokens = match fragment.get_for_attribute() {
+                Some(for_expr) => generate_for(for_expr, quote! { for_v.extend(#nodes); }, mode),
+                None => nodes,
+            };
+
+            match fragment.get_if_attribute() {
+                Some(if_expr) => quote! {
+                    if #if_expr {
+                        #tokens
+                    } else {
+                        vec![]
+                    }
+                },
+                None => tokens,
+            }
+        }
+        Node::RawText(raw_text) => {
+            let (escape, to_value) = match raw_text.language {
+                RawTextLanguage::JavaScript => (
+                    quote! { hypersynthetic::escape::escape_js_string },
+                    quote! { to_js_value },
+                ),
+                RawTextLanguage::Css => (
+                    quote! { hypersynthetic::escape::escape_css_string },
+                    quote! { to_css_value },
+                ),
+            };
+            let interpolated: Vec<TokenStream2> = raw_text
+                .segments
+                .iter()
+                .map(|segment| match segment {
+                    RawSegment::Str(s) => quote! { #s },
+                    RawSegment::InString(e) => quote! { #escape(&format!("{}", #e)) },
+                    RawSegment::Value(e) => quote! {
+                        {
+                            use hypersynthetic::escape::{
+                                DisplayRawTextValue as _, TrustedRawTextValue as _,
+                            };
+                            (&hypersynthetic::escape::RawTextValue(&#e)).#to_value()
+                        }
+                    },
+                })
+                .collect();
+            let format_pattern = generate_format_string_pattern(interpolated.len());
+            quote! {
+                vec![hypersynthetic::Node::Text(format!(#format_pattern, #(#interpolated),*))]
+            }
+        }
+        // Slots are extracted from the children of components while parsing
+        Node::Slot(_) => unreachable!(),
+        Node::Match(match_node) => {
+            let expr = match_node.expr;
+            let arms = match_node.arms.into_iter().map(|(case, node)| {
+                let pat = case.pat;
+                let guard = case.guard.map(|guard| quote! { if #guard });
+                let body = generate_node(node, mode);
+                quote! { #pat #guard => { #body } }
+            });
+            quote! {
+                match #expr {
+                    #(#arms)*
+                }
+            }
+        }
+        Node::Conditional(conditional) => {
+            let conditions = conditional.branches.iter().map(|(condition, _)| condition);
+            let branches: Vec<TokenStream2> = conditional
+                .branches
+                .iter()
+                .map(|(_, node)| generate_node(node.clone(), mode))
+                .collect();
+            let else_branch = match conditional.else_branch {
+                Some(node) => generate_node(*node, mode),
+                None => quote! { vec![] },
+            };
+            quote! {
+                #(if #conditions { #branches } else)* { #else_branch }
+            }
+        }
         Node::Component(component) => {
-            let component_name = &component.name;
+            // `<Table<Row>>` is called as `Table::<Row>`
+            let mut component_name = component.name.clone();
+            for segment in &mut component_name.segments {
+                if let syn::PathArguments::AngleBracketed(args) = &mut segment.arguments {
+                    args.colon2_token = Some(Default::default());
+                }
+            }
+            let component_name = &component_name;
             let attributes = component.get_regular_attributes();
 
-            // Generate builder method calls
-            let builder_calls: Vec<TokenStream2> = attributes
+            // Every attribute goes to the props builder. The builders of components declared
+            // with `#[component(rest_attrs)]` also accept attributes that aren't props.
+            let mut errors = Vec::new();
+            let builder_steps: Vec<(String, Option<TokenStream2>)> = attributes
                 .iter()
-                .map(|attr| {
-                    // Extract the attribute name
-                    let attr_name = match &attr.name {
-                        AttrName::Literal(name) => {
-                            let name_str = name.value();
-                            quote::format_ident!("{}", name_str)
-                        }
-                        AttrName::Expression(_) => {
-                            // This shouldn't happen for component props
-                            panic!("Component props must have literal names")
+                .filter_map(|attr| {
+                    let name_str = match &attr.name {
+                        AttrName::Literal(name) => name.value(),
+                        AttrName::Expression(expr) => {
+                            errors.push(syn::Error::new(
+                                expr.span(),
+                                "Component props must have literal names",
+                            ));
+                            return None;
                         }
                     };
 
                     // Extract the attribute value
                     let attr_value = match &attr.value {
-                        Some(AttrValue::Literal(value)) => quote! { #value },
-                        Some(AttrValue::Expression(expr)) => quote! { #expr },
+                        Some(AttrValue::Literal(value)) => Some(quote! { #value }),
+                        Some(AttrValue::Expression(expr)) => Some(quote! { #expr }),
+                        Some(AttrValue::Toggle(expr)) => {
+                            errors.push(syn::Error::new(
+                                expr.span(),
+                                "`:if` attributes can't be used as component props",
+                            ));
+                            return None;
+                        }
                         Some(AttrValue::Interpolated(segments)) => {
                             // For interpolated values, we need to generate the interpolation
                             let interpolated: Vec<TokenStream2> = segments
@@ -137,59 +437,140 @@ fn generate_node(tag: Node) -> TokenStream2 {
                                 })
                                 .collect();
                             let format_pattern = generate_format_string_pattern(interpolated.len());
-                            quote! { format!(#format_pattern, #(#interpolated),*) }
+                            Some(quote! { format!(#format_pattern, #(#interpolated),*) })
                         }
-                        None => quote! {},
+                        None => None,
                     };
 
-                    quote! { .#attr_name(#attr_value) }
+                    Some((name_str, attr_value))
                 })
                 .collect();
+            if !errors.is_empty() {
+                return errors
+                    .into_iter()
+                    .map(|error| error.to_compile_error())
+                    .collect();
+            }
 
-            let children: TokenStream2 =
-                generate_nodes(NodeCollection::Nodes(component.children.clone()));
+            // Children and slots are rendered before the props builder, outside of the scope
+            // of the rest attributes trait, which would clash with the one of a component in them
+            let mut slot_contents = Vec::new();
+            let mut slot_calls = Vec::new();
+            for (i, slot) in component.slots.clone().into_iter().enumerate() {
+                let name = slot.name;
+                let var = quote::format_ident!("slot_v{}", i);
+                slot_contents.push(generate_children(slot.children, mode));
+                slot_calls.push(quote! { .#name(#var) });
+            }
+            let slot_vars: Vec<syn::Ident> = (0..slot_contents.len())
+                .map(|i| quote::format_ident!("slot_v{}", i))
+                .collect();
+
+            let children: TokenStream2 = generate_children(component.children.clone(), mode);
             let has_slots = !component.children.is_empty();
 
-            // For slots, we use the ComponentWithSlots system
+            let props = |props_builder: TokenStream2| {
+                let builder = builder_steps.iter().fold(
+                    quote! { #props_builder(&#component_name) },
+                    |builder, (name, value)| generate_builder_step(builder, name, value.as_ref()),
+                );
+                quote! { #builder #(#slot_calls)*.build() }
+            };
+
+            // For slots, we use the ComponentWithSlots system. In `html_async!` the Render
+            // system accepts components that return futures as well.
+            let is_async = mode.is_async;
+            let is_try = mode.try_depth > 0 && !is_async;
             let final_call = if has_slots {
+                let (props_builder, view) = if is_async || is_try {
+                    (
+                        quote! { hypersynthetic::component::render_with_slots_props_builder },
+                        quote! { hypersynthetic::component::render_with_slots_view },
+                    )
+                } else {
+                    (
+                        quote! { hypersynthetic::component::component_with_slots_props_builder },
+                        quote! { hypersynthetic::component::component_with_slots_view },
+                    )
+                };
+                let props = props(props_builder);
                 quote! {
-                    hypersynthetic::component::component_with_slots_view(
+                    #view(
                         &#component_name,
-                        #children,
-                        hypersynthetic::component::component_with_slots_props_builder(&#component_name)
-                            #(#builder_calls)*
-                            .build()
+                        children_v,
+                        #props
                     )
                 }
             } else {
+                let (props_builder, view) = if is_async || is_try {
+                    (
+                        quote! { hypersynthetic::component::render_props_builder },
+                        quote! { hypersynthetic::component::render_view },
+                    )
+                } else {
+                    (
+                        quote! { hypersynthetic::component::component_with_optional_slot_props_builder },
+                        quote! { hypersynthetic::component::component_with_optional_slot_view },
+                    )
+                };
+                let props = props(props_builder);
                 quote! {
-                    hypersynthetic::component::component_view(
+                    #view(
                         &#component_name,
-                        hypersynthetic::component::component_props_builder(&#component_name)
-                            #(#builder_calls)*
-                            .build()
+                        #props
                     )
                 }
             };
-
-            let tokens = if component.has_for_attribute() {
-                let for_expr = component.get_for_attribute();
-                let var = for_expr.pat;
-                let collection = for_expr.collection;
+            let nodes = if is_try {
+                let label = try_label(mode);
+                quote! {
+                    match hypersynthetic::component::into_component_result(#final_call) {
+                        ::std::result::Result::Ok(fragment) => fragment.get_nodes(),
+                        ::std::result::Result::Err(error) => break #label ::std::result::Result::Err(error),
+                    }
+                }
+            } else if is_async {
+                // Components in the children are deferred while the call is evaluated
                 quote! {
                     {
-                        let mut for_v = Vec::new();
-                        for #var in #collection {
-                            for_v.extend(#final_call.get_nodes());
-                        }
-                        for_v
+                        let rendered = #final_call;
+                        pending_components.defer(rendered)
                     }
                 }
             } else {
-                quote! {
-                    #final_call.get_nodes()
+                quote! { #final_call.get_nodes() }
+            };
+            let rest_attributes_trait = generate_rest_attributes_trait(
+                &builder_steps
+                    .iter()
+                    .filter(|(_, value)| value.is_some())
+                    .map(|(name, _)| name)
+                    .collect::<Vec<_>>(),
+            );
+            let (children, children_pat) = if has_slots {
+                (children, quote! { children_v })
+            } else {
+                (quote! { () }, quote! { _ })
+            };
+            let nodes = quote! {
+                match (#children, #(#slot_contents,)*) {
+                    (#children_pat, #(#slot_vars,)*) => {
+                        #rest_attributes_trait
+                        #nodes
+                    }
                 }
             };
+            let nodes = with_let_bindings(&component.props, nodes);
+
+            let tokens = if component.has_for_attribute() {
+                generate_for(
+                    component.get_for_attribute(),
+                    quote! { for_v.extend(#nodes); },
+                    mode,
+                )
+            } else {
+                nodes
+            };
 
             if component.has_if_attribute() {
                 let if_expr = component.get_if_attribute();
@@ -207,45 +588,590 @@ fn generate_node(tag: Node) -> TokenStream2 {
     }
 }
 
+/// Renders a node at compile time if it contains no expressions, components, `:if` or `:for`.
+/// The output must match what the runtime renderer would produce for the generated node.
+fn static_html(node: &Node) -> Option<String> {
+    match node {
+        Node::Element(element) => {
+            let only_literal_attributes = element
+                .attributes
+                .iter()
+                .all(|attr| matches!(attr, Attribute::RegularAttribute(_)));
+            if !only_literal_attributes {
+                return None;
+            }
+
+            let tag_name = element.tag_name.to_string();
+            let mut html = format!("<{tag_name}");
+            for attr in element.get_regular_attributes() {
+                let AttrName::Literal(name) = &attr.name else {
+                    return None;
+                };
+                match &attr.value {
+                    Some(AttrValue::Literal(value)) => {
+                        let value = htmlize::escape_attribute(value.value());
+                        html.push_str(&format!(" {}=\"{}\"", name.value(), value));
+                    }
+                    None => html.push_str(&format!(" {}", name.value())),
+                    Some(_) => return None,
+                }
+            }
+
+            if element.self_closing {
+                html.push_str(" />");
+                return Some(html);
+            }
+
+            html.push('>');
+            for child in &element.children {
+                html.push_str(&static_html(child)?);
+            }
+            html.push_str(&format!("</{tag_name}>"));
+            Some(html)
+        }
+        // Text literals go through `format!`, so only the ones without braces are static
+        Node::Text(text) => {
+            let text = text.value();
+            if text.contains(['{', '}']) {
+                None
+            } else {
+                Some(htmlize::escape_text(text).into_owned())
+            }
+        }
+        // Bare text is escaped by the runtime, so only the text it would keep as it is is static
+        Node::BareText(text) => {
+            let text = text.value();
+            if text.contains(['&', '<', '>']) {
+                None
+            } else {
+                Some(text)
+            }
+        }
+        Node::RawText(raw_text) => raw_text
+            .segments
+            .iter()
+            .map(|segment| match segment {
+                RawSegment::Str(s) => Some(s.value()),
+                RawSegment::InString(_) | RawSegment::Value(_) => None,
+            })
+            .collect(),
+        Node::Fragment(fragment) if fragment.attributes.is_empty() => {
+            fragment.children.iter().map(static_html).collect()
+        }
+        Node::DocType => Some("<!DOCTYPE html>".to_owned()),
+        // Comments are escaped by the runtime as well, so only the ones it keeps as they are
+        // are static
+        Node::Comment(segments) => {
+            let text: String = segments
+                .iter()
+                .map(|segment| match segment {
+                    InterpolatedSegment::Str(s) => Some(s.value()),
+                    InterpolatedSegment::Expr(_) => None,
+                })
+                .collect::<Option<_>>()?;
+            if text.starts_with('>') || text.contains('-') {
+                None
+            } else {
+                Some(format!("<!--{text}-->"))
+            }
+        }
+        Node::Component(_)
+        | Node::Conditional(_)
+        | Node::Expression(_)
+        | Node::Fragment(_)
+        | Node::Match(_)
+        | Node::Slot(_)
+        | Node::UnescapedExpression(_) => None,
+    }
+}
+
+fn generate_attributes(attributes: &[Attribute]) -> TokenStream2 {
+    let class_directives: Vec<&ClassDirective> = attributes
+        .iter()
+        .filter_map(|attr| match attr {
+            Attribute::Class(class) => Some(class),
+            _ => None,
+        })
+        .collect();
+    let style_directives: Vec<&StyleDirective> = attributes
+        .iter()
+        .filter_map(|attr| match attr {
+            Attribute::Style(style) => Some(style),
+            _ => None,
+        })
+        .collect();
+    let static_values = |name: &str| -> Vec<&AttrValue> {
+        attributes
+            .iter()
+            .filter_map(|attr| match attr {
+                Attribute::RegularAttribute(RegularAttribute {
+                    name: AttrName::Literal(lit),
+                    value,
+                }) if lit.value() == name => value.as_ref(),
+                _ => None,
+            })
+            .collect()
+    };
+    let is_merged = |attr: &RegularAttribute, name: &str, directives: usize| {
+        directives > 0 && matches!(&attr.name, AttrName::Literal(lit) if lit.value() == name)
+    };
+
+    // When there are directives, `class` and `style` attributes are merged with them
+    // and rendered once, where the first of them appears.
+    let mut class_rendered = false;
+    let mut style_rendered = false;
+    let mut pushes = Vec::new();
+    for attr in attributes {
+        let (is_class, is_style) = match attr {
+            Attribute::RegularAttribute(attr) => (
+                is_merged(attr, "class", class_directives.len()),
+                is_merged(attr, "style", style_directives.len()),
+            ),
+            Attribute::Class(_) => (true, false),
+            Attribute::Style(_) => (false, true),
+            Attribute::Spread(expr) => {
+                pushes.push(quote! {
+                    {
+                        use hypersynthetic::attribute_value::SpreadAttributes as _;
+                        hypersynthetic::attribute_value::Spread(#expr).spread_into(&mut attributes_v);
+                    }
+                });
+                continue;
+            }
+            _ => continue,
+        };
+
+        if is_class {
+            if !class_rendered {
+                pushes.push(generate_class_list(
+                    &static_values("class"),
+                    &class_directives,
+                ));
+                class_rendered = true;
+            }
+        } else if is_style {
+            if !style_rendered {
+                pushes.push(generate_style_list(
+                    &static_values("style"),
+                    &style_directives,
+                ));
+                style_rendered = true;
+            }
+        } else if let Attribute::RegularAttribute(attr) = attr {
+            pushes.push(generate_attribute(attr.clone()));
+        }
+    }
+
+    quote! {
+        {
+            let mut attributes_v = Vec::new();
+            #(#pushes)*
+            attributes_v
+        }
+    }
+}
+
+fn generate_class_list(
+    static_values: &[&AttrValue],
+    directives: &[&ClassDirective],
+) -> TokenStream2 {
+    let static_values = static_values.iter().map(|value| {
+        let value = generate_attr_value_string(
+            value,
+            &quote! { hypersynthetic::escape::AttributeContext::Plain },
+        );
+        quote! {
+            if let Some(attr_value) = #value {
+                class_list.add(&attr_value);
+            }
+        }
+    });
+    let directives = directives.iter().map(|directive| {
+        let name = &directive.name;
+        let condition = &directive.condition;
+        quote! {
+            if #condition {
+                class_list.add(#name);
+            }
+        }
+    });
+    quote! {
+        {
+            let mut class_list = hypersynthetic::attribute_value::ClassList::default();
+            #(#static_values)*
+            #(#directives)*
+            if let Some(attr_value) = class_list.to_attribute_value() {
+                hypersynthetic::attribute_value::merge_attribute(
+                    &mut attributes_v,
+                    "class".to_owned(),
+                    Some(hypersynthetic::escape_attribute(attr_value).to_string()),
+                );
+            }
+        }
+    }
+}
+
+fn generate_style_list(
+    static_values: &[&AttrValue],
+    directives: &[&StyleDirective],
+) -> TokenStream2 {
+    let static_values = static_values.iter().map(|value| {
+        let value = generate_attr_value_string(
+            value,
+            &quote! { hypersynthetic::escape::AttributeContext::Css },
+        );
+        quote! {
+            if let Some(attr_value) = #value {
+                style_list.add_declarations(&attr_value);
+            }
+        }
+    });
+    let directives = directives.iter().map(|directive| {
+        let property = &directive.property;
+        let value = generate_attr_value_string(
+            &directive.value,
+            &quote! { hypersynthetic::escape::AttributeContext::CssValue },
+        );
+        quote! {
+            if let Some(attr_value) = #value {
+                style_list.set(#property, &attr_value);
+            }
+        }
+    });
+    quote! {
+        {
+            let mut style_list = hypersynthetic::attribute_value::StyleList::default();
+            #(#static_values)*
+            #(#directives)*
+            if let Some(attr_value) = style_list.to_attribute_value() {
+                hypersynthetic::attribute_value::merge_attribute(
+                    &mut attributes_v,
+                    "style".to_owned(),
+                    Some(hypersynthetic::escape_attribute(attr_value).to_string()),
+                );
+            }
+        }
+    }
+}
+
+/// Generates an `Option<String>` expression with the attribute value, escaped for `context`,
+/// an expression of the runtime `AttributeContext`, but not yet for HTML.
+fn generate_attr_value_string(value: &AttrValue, context: &TokenStream2) -> TokenStream2 {
+    match value {
+        AttrValue::Literal(value) => quote! { Some(#value.to_owned()) },
+        AttrValue::Expression(expr) => {
+            quote! {
+                {
+                    use hypersynthetic::attribute_value::{
+                        DisplayAttributeValue as _, OptionalAttributeValue as _, TrustedAttributeValue as _,
+                    };
+                    (&hypersynthetic::attribute_value::AttributeValue(&#expr)).to_attribute_value(#context)
+                }
+            }
+        }
+        AttrValue::Interpolated(segments) => {
+            let format_call = interpolate(segments, context);
+            quote! { Some(#format_call) }
+        }
+        AttrValue::Toggle(condition) => {
+            syn::Error::new(condition.span(), "`:if` can't be used here").to_compile_error()
+        }
+    }
+}
+
+/// Generates a statement that pushes the attribute into `attributes_v`,
+/// unless its value says that it must be omitted. The values of expressions are escaped for
+/// the context of the attribute, which is looked up by its name at runtime.
 fn generate_attribute(attr: RegularAttribute) -> TokenStream2 {
     let attr_name = match &attr.name {
         AttrName::Literal(name) => quote! { #name.to_owned() },
-        AttrName::Expression(expr) => {
-            quote! { hypersynthetic::escape_attribute(format!("{}", #expr)).to_string() }
+        AttrName::Expression(_) => {
+            quote! { hypersynthetic::escape_attribute(&attr_name).to_string() }
         }
     };
+    let context = quote! { attr_context };
+    let is_boolean = matches!(
+        &attr.name,
+        AttrName::Literal(name) if BOOLEAN_ATTRIBUTES.contains(&name.value().to_lowercase().as_str())
+    );
+    let is_hidden = matches!(
+        &attr.name,
+        AttrName::Literal(name) if name.value().eq_ignore_ascii_case("hidden")
+    );
 
-    let attr_value = match &attr.value {
+    let push = |value: TokenStream2| {
+        quote! {
+            attributes_v.push(hypersynthetic::Attribute {
+                name: #attr_name,
+                value: #value,
+            });
+        }
+    };
+
+    let toggle = |condition: &syn::Expr| {
+        let push = push(quote! { None });
+        quote! {
+            if #condition {
+                #push
+            }
+        }
+    };
+
+    let statement = match &attr.value {
         Some(AttrValue::Literal(value)) => {
-            quote! { Some(hypersynthetic::escape_attribute(#value).to_string()) }
+            push(quote! { Some(hypersynthetic::escape_attribute(#value).to_string()) })
+        }
+        Some(AttrValue::Toggle(condition)) => toggle(condition),
+        Some(AttrValue::Expression(condition)) if is_boolean => toggle(condition),
+        Some(AttrValue::Expression(value)) if is_hidden => {
+            let push = push(quote! {
+                hidden.map(|hidden| hypersynthetic::escape_attribute(hidden).to_string())
+            });
+            quote! {
+                let hidden = {
+                    use hypersynthetic::attribute_value::{
+                        DisplayHiddenValue as _, OptionalHiddenValue as _, ToggleHiddenValue as _,
+                    };
+                    (&hypersynthetic::attribute_value::AttributeValue(&#value)).to_hidden_value()
+                };
+                if let Some(hidden) = hidden {
+                    #push
+                }
+            }
         }
-        Some(AttrValue::Expression(expr)) => {
-            quote! { Some(hypersynthetic::escape_attribute(format!("{}", #expr)).to_string()) }
+        Some(value @ AttrValue::Expression(_)) => {
+            let value = generate_attr_value_string(value, &context);
+            let push =
+                push(quote! { Some(hypersynthetic::escape_attribute(attr_value).to_string()) });
+            quote! {
+                if let Some(attr_value) = #value {
+                    #push
+                }
+            }
         }
-        Some(AttrValue::Interpolated(segments)) => interpolate_attr_value(segments),
-        None => quote! { None },
+        Some(AttrValue::Interpolated(segments)) => push(interpolate_attr_value(segments, &context)),
+        None => push(quote! { None }),
     };
 
-    quote! {
-        hypersynthetic::Attribute {
-            name: #attr_name,
-            value: #attr_value,
+    let has_context = match &attr.value {
+        Some(AttrValue::Expression(_)) => !is_boolean && !is_hidden,
+        Some(AttrValue::Interpolated(_)) => true,
+        _ => false,
+    };
+    match &attr.name {
+        AttrName::Literal(name) if has_context => quote! {
+            {
+                let attr_context = hypersynthetic::escape::AttributeContext::of_attribute(#name);
+                #statement
+            }
+        },
+        AttrName::Literal(_) => statement,
+        AttrName::Expression(expr) => {
+            let attr_context = has_context.then(|| {
+                quote! {
+                    let attr_context = hypersynthetic::escape::AttributeContext::of_attribute(&attr_name);
+                }
+            });
+            quote! {
+                {
+                    let attr_name = format!("{}", #expr);
+                    #attr_context
+                    #statement
+                }
+            }
         }
     }
 }
 
-fn interpolate_attr_value(segments: &[InterpolatedSegment]) -> TokenStream2 {
+fn interpolate_attr_value(
+    segments: &[InterpolatedSegment],
+    context: &TokenStream2,
+) -> TokenStream2 {
+    let format_call = interpolate(segments, context);
+    quote! { Some(hypersynthetic::escape_attribute(#format_call).to_string()) }
+}
+
+/// Generates a `String` expression with the segments joined, each value escaped for `context`.
+/// A URL is checked as a whole when a value comes before the end of its scheme.
+fn interpolate(segments: &[InterpolatedSegment], context: &TokenStream2) -> TokenStream2 {
     let interpolated: Vec<TokenStream2> = segments
         .iter()
         .map(|segment| match segment {
             InterpolatedSegment::Str(s) => quote! { #s },
-            InterpolatedSegment::Expr(e) => quote! { format!("{}", #e) },
-            // InterpolatedSegment::Expr(e) => quote! { #e },
+            InterpolatedSegment::Expr(e) => quote! {
+                {
+                    use hypersynthetic::attribute_value::{DisplayAttributeValue as _, TrustedSegmentValue as _};
+                    (&hypersynthetic::attribute_value::AttributeValue(&#e)).to_segment_value(#context)
+                }
+            },
         })
         .collect();
     let format_pattern = generate_format_string_pattern(interpolated.len());
     let format_call = quote! { format!(#format_pattern, #(#interpolated),*) };
-    quote! { Some(hypersynthetic::escape_attribute(#format_call).to_string()) }
+
+    let mut scheme_is_dynamic = false;
+    for segment in segments {
+        match segment {
+            InterpolatedSegment::Str(s) if s.value().contains([':', '/', '?', '#']) => break,
+            InterpolatedSegment::Str(_) => {}
+            InterpolatedSegment::Expr(_) => {
+                scheme_is_dynamic = true;
+                break;
+            }
+        }
+    }
+    if scheme_is_dynamic {
+        quote! {
+            {
+                let attr_value = #format_call;
+                if #context == hypersynthetic::escape::AttributeContext::Url {
+                    hypersynthetic::escape::sanitize_url(&attr_value).to_owned()
+                } else {
+                    attr_value
+                }
+            }
+        }
+    } else {
+        format_call
+    }
+}
+
+/// `body` adds the nodes of one iteration to `for_v`.
+fn generate_for(for_expr: ForExpr, body: TokenStream2, mode: Mode) -> TokenStream2 {
+    let ForExpr {
+        pat,
+        collection,
+        info,
+        empty,
+    } = for_expr;
+    let empty = empty.map(|node| {
+        let nodes = generate_node(*node, mode);
+        quote! {
+            if for_index == 0 {
+                for_v.extend(#nodes);
+            }
+        }
+    });
+
+    match info {
+        Some(info) => quote! {
+            {
+                let mut for_v = Vec::new();
+                let mut for_iter = ::std::iter::IntoIterator::into_iter(#collection).peekable();
+                let mut for_index = 0;
+                // Bound like in a `for` loop, so a refutable pattern is an error
+                while let Some(for_item) = for_iter.next() {
+                    let #pat = for_item;
+                    let #info = hypersynthetic::LoopInfo::new(for_index, for_iter.peek().is_none());
+                    for_index += 1;
+                    #body
+                }
+                #empty
+                for_v
+            }
+        },
+        None if empty.is_some() => quote! {
+            {
+                let mut for_v = Vec::new();
+                let mut for_index = 0;
+                for #pat in #collection {
+                    for_index += 1;
+                    #body
+                }
+                #empty
+                for_v
+            }
+        },
+        None => quote! {
+            {
+                let mut for_v = Vec::new();
+                for #pat in #collection {
+                    #body
+                }
+                for_v
+            }
+        },
+    }
+}
+
+/// Wraps the tokens of a node in a block that starts with its `:let` bindings, in order.
+fn with_let_bindings(attributes: &[Attribute], tokens: TokenStream2) -> TokenStream2 {
+    let bindings: Vec<&LetBinding> = attributes
+        .iter()
+        .filter_map(|attr| match attr {
+            Attribute::Let(binding) => Some(binding),
+            _ => None,
+        })
+        .collect();
+    if bindings.is_empty() {
+        return tokens;
+    }
+
+    let pats = bindings.iter().map(|binding| &binding.pat);
+    let exprs = bindings.iter().map(|binding| &binding.expr);
+    quote! {
+        {
+            #(let #pats = #exprs;)*
+            #tokens
+        }
+    }
+}
+
+/// Passes an attribute to the props builder of a component. Attributes whose names aren't
+/// identifiers, like `hx-post`, and attributes without a value can only be rest attributes.
+/// An attribute named like an identifier calls the setter of the prop with its name, or the
+/// method of [generate_rest_attributes_trait] when there is no such prop.
+fn generate_builder_step(
+    builder: TokenStream2,
+    name: &str,
+    value: Option<&TokenStream2>,
+) -> TokenStream2 {
+    match (syn::parse_str::<syn::Ident>(name), value) {
+        (Ok(method), Some(value)) => quote! { #builder.#method(#value) },
+        (_, Some(value)) => quote! {
+            hypersynthetic::component::AcceptsRestAttributes::rest_attribute(
+                #builder, #name, #value,
+            )
+        },
+        (_, None) => quote! {
+            hypersynthetic::component::AcceptsRestAttributes::rest_flag(#builder, #name)
+        },
+    }
+}
+
+/// A trait with a method for every attribute named like an identifier, which passes it as a
+/// rest attribute. Setters of props are inherent methods of the builder, so they are called
+/// instead when there is a prop with the name.
+fn generate_rest_attributes_trait(names: &[&String]) -> TokenStream2 {
+    let mut methods: Vec<syn::Ident> = names
+        .iter()
+        .filter_map(|name| syn::parse_str::<syn::Ident>(name).ok())
+        .collect();
+    methods.sort_by_key(|method| method.to_string());
+    methods.dedup();
+    if methods.is_empty() {
+        return quote! {};
+    }
+    let names = methods.iter().map(|method| method.to_string());
+
+    quote! {
+        trait RestAttributes: Sized {
+            #(
+                fn #methods(
+                    self,
+                    value: impl hypersynthetic::component::RestAttributeValue,
+                ) -> Self
+                where
+                    Self: hypersynthetic::component::AcceptsRestAttributes,
+                {
+                    hypersynthetic::component::AcceptsRestAttributes::rest_attribute(
+                        self, #names, value,
+                    )
+                }
+            )*
+        }
+        impl<B> RestAttributes for B {}
+    }
 }
 
 fn generate_format_string_pattern(count: usize) -> TokenStream2 {
@@ -2,13 +2,16 @@ mod attributes;
 mod generator;
 mod nodes;
 mod parser;
+mod props;
 mod utils;
 
 extern crate proc_macro;
 
-use generator::generate_nodes;
+use generator::{generate_async_nodes, generate_nodes, generate_try_nodes};
 use nodes::NodeCollection;
 use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use props::{PropOptions, is_prop_attribute};
 use quote::quote;
 use syn::{ItemFn, parse_macro_input};
 use utils::is_pascal_case;
@@ -20,11 +23,38 @@ pub fn html(input: TokenStream) -> TokenStream {
     TokenStream::from(expanded)
 }
 
+#[proc_macro]
+pub fn try_html(input: TokenStream) -> TokenStream {
+    let parsed_html_nodes = parse_macro_input!(input as NodeCollection);
+    let expanded = generate_try_nodes(parsed_html_nodes);
+    TokenStream::from(expanded)
+}
+
+#[proc_macro]
+pub fn html_async(input: TokenStream) -> TokenStream {
+    let parsed_html_nodes = parse_macro_input!(input as NodeCollection);
+    let expanded = generate_async_nodes(parsed_html_nodes);
+    TokenStream::from(expanded)
+}
+
 #[proc_macro_attribute]
-pub fn component(_attr: TokenStream, item: TokenStream) -> TokenStream {
+pub fn component(attr: TokenStream, item: TokenStream) -> TokenStream {
     // Parse the input TokenStream into a syn::ItemFn
     let mut function: ItemFn = syn::parse(item.clone()).unwrap();
 
+    // `#[component(rest_attrs)]` collects attributes that are not props into its `Attributes`
+    // parameter
+    let mut rest_attrs = false;
+    let options_parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("rest_attrs") {
+            rest_attrs = true;
+            Ok(())
+        } else {
+            Err(meta.error("expected `rest_attrs`"))
+        }
+    });
+    parse_macro_input!(attr with options_parser);
+
     // Check if the function's identifier is PascalCase
     let fn_name = &function.sig.ident;
     if !is_pascal_case(fn_name) {
@@ -42,7 +72,10 @@ pub fn component(_attr: TokenStream, item: TokenStream) -> TokenStream {
     // Helper function to check if a type contains any references
     fn type_contains_refs(ty: &syn::Type) -> bool {
         match ty {
-            syn::Type::Reference(_) => true,
+            // Only references without a lifetime need one
+            syn::Type::Reference(type_ref) => {
+                type_ref.lifetime.is_none() || type_contains_refs(&type_ref.elem)
+            }
             syn::Type::Path(type_path) => {
                 // Check generic arguments
                 type_path.path.segments.iter().any(|segment| {
@@ -103,6 +136,47 @@ pub fn component(_attr: TokenStream, item: TokenStream) -> TokenStream {
         }
     }
 
+    // Helper function to replace `impl Trait` in a type with a new type parameter
+    fn replace_impl_traits(ty: &mut syn::Type, generics: &mut syn::Generics) {
+        match ty {
+            syn::Type::ImplTrait(impl_trait) => {
+                let ident = quote::format_ident!("__Impl{}", generics.params.len());
+                let bounds = &impl_trait.bounds;
+                generics.params.push(syn::parse_quote!(#ident: #bounds));
+                *ty = syn::parse_quote!(#ident);
+            }
+            syn::Type::Reference(type_ref) => replace_impl_traits(&mut type_ref.elem, generics),
+            syn::Type::Path(type_path) => {
+                for segment in &mut type_path.path.segments {
+                    if let syn::PathArguments::AngleBracketed(args) = &mut segment.arguments {
+                        for arg in &mut args.args {
+                            if let syn::GenericArgument::Type(inner_ty) = arg {
+                                replace_impl_traits(inner_ty, generics);
+                            }
+                        }
+                    }
+                }
+            }
+            syn::Type::Tuple(type_tuple) => {
+                for elem in &mut type_tuple.elems {
+                    replace_impl_traits(elem, generics);
+                }
+            }
+            syn::Type::Array(type_array) => replace_impl_traits(&mut type_array.elem, generics),
+            syn::Type::Slice(type_slice) => replace_impl_traits(&mut type_slice.elem, generics),
+            syn::Type::Paren(type_paren) => replace_impl_traits(&mut type_paren.elem, generics),
+            syn::Type::Group(type_group) => replace_impl_traits(&mut type_group.elem, generics),
+            _ => {}
+        }
+    }
+
+    // `impl Trait` parameters become type parameters, so that the props struct can hold them
+    for input in &mut function.sig.inputs {
+        if let syn::FnArg::Typed(pat_type) = input {
+            replace_impl_traits(&mut pat_type.ty, &mut function.sig.generics);
+        }
+    }
+
     // Add lifetime annotations to the original function if needed
     if function.sig.generics.lifetimes().count() == 0 {
         // Check if any parameter has a reference
@@ -123,7 +197,7 @@ pub fn component(_attr: TokenStream, item: TokenStream) -> TokenStream {
                 colon_token: None,
                 bounds: syn::punctuated::Punctuated::new(),
             });
-            function.sig.generics.params.push(lifetime_param);
+            function.sig.generics.params.insert(0, lifetime_param);
 
             // Update reference types to use the lifetime
             for input in &mut function.sig.inputs {
@@ -138,18 +212,34 @@ pub fn component(_attr: TokenStream, item: TokenStream) -> TokenStream {
     let props_name = quote::format_ident!("{}Props", fn_name);
     let props_builder_name = quote::format_ident!("{}PropsBuilder", fn_name);
 
+    fn is_html_fragment(ty: &syn::Type) -> bool {
+        if let syn::Type::Path(type_path) = ty {
+            type_path
+                .path
+                .segments
+                .last()
+                .is_some_and(|seg| seg.ident == "HtmlFragment")
+        } else {
+            false
+        }
+    }
+
+    fn is_rest_attrs_param(param: &syn::PatType) -> bool {
+        if let syn::Type::Path(type_path) = &*param.ty {
+            type_path
+                .path
+                .segments
+                .last()
+                .is_some_and(|seg| seg.ident == "Attributes")
+        } else {
+            false
+        }
+    }
+
     // Check if the first parameter is HtmlFragment (slot)
     let has_slot = function.sig.inputs.first().is_some_and(|arg| {
         if let syn::FnArg::Typed(pat_type) = arg {
-            if let syn::Type::Path(type_path) = &*pat_type.ty {
-                type_path
-                    .path
-                    .segments
-                    .last()
-                    .is_some_and(|seg| seg.ident == "HtmlFragment")
-            } else {
-                false
-            }
+            is_html_fragment(&pat_type.ty)
         } else {
             false
         }
@@ -170,14 +260,51 @@ pub fn component(_attr: TokenStream, item: TokenStream) -> TokenStream {
         })
         .collect();
 
-    // Generate struct fields
-    let struct_fields = params.iter().map(|param| {
+    // Generate struct fields. The rest of HtmlFragment parameters are named slots,
+    // which are empty unless filled.
+    let mut struct_fields = Vec::new();
+    for param in &params {
         let pat = &param.pat;
         let ty = &param.ty;
-        quote! {
-            #pat: #ty
+        let mut options = match PropOptions::from_attributes(&param.attrs) {
+            Ok(options) => options,
+            Err(err) => return err.to_compile_error().into(),
+        };
+        if is_html_fragment(ty) && options.default.is_none() {
+            options.default = Some(None);
         }
-    });
+        let builder_attribute = if rest_attrs && is_rest_attrs_param(param) {
+            quote! {
+                #[builder(via_mutators, mutators(
+                    pub fn add_rest_attribute(
+                        &mut self,
+                        name: &str,
+                        value: impl hypersynthetic::component::RestAttributeValue,
+                    ) {
+                        value.set_rest_attribute(&mut self.#pat, name);
+                    }
+                    pub fn add_rest_flag(&mut self, name: &str) {
+                        self.#pat.set_flag(name);
+                    }
+                ))]
+            }
+        } else {
+            options.builder_attribute()
+        };
+        struct_fields.push(quote! {
+            #builder_attribute
+            #pat: #ty
+        });
+    }
+
+    if rest_attrs && !params.iter().any(|param| is_rest_attrs_param(param)) {
+        return syn::Error::new(
+            function.sig.ident.span(),
+            "`rest_attrs` components need an `Attributes` parameter",
+        )
+        .to_compile_error()
+        .into();
+    }
 
     // Generate the internal function name
     let internal_fn_name = quote::format_ident!("__{}", fn_name);
@@ -187,6 +314,13 @@ pub fn component(_attr: TokenStream, item: TokenStream) -> TokenStream {
     internal_function.sig.ident = internal_fn_name.clone();
     internal_function.vis = syn::Visibility::Inherited;
 
+    // `#[prop]` only exists for the props struct
+    for input in &mut internal_function.sig.inputs {
+        if let syn::FnArg::Typed(pat_type) = input {
+            pat_type.attrs.retain(|attr| !is_prop_attribute(attr));
+        }
+    }
+
     // Add allow directive for snake_case to the internal function
     let allow_attr: syn::Attribute = syn::parse_quote!(#[allow(non_snake_case)]);
     internal_function.attrs.push(allow_attr);
@@ -198,13 +332,50 @@ pub fn component(_attr: TokenStream, item: TokenStream) -> TokenStream {
     // Generate the parameter unpacking
     let param_names: Vec<_> = params.iter().map(|param| &param.pat).collect();
 
+    // Type and const parameters are passed explicitly, since const parameters can't be
+    // inferred. Lifetimes are left out so that late-bound ones still work.
+    let explicit_args: Vec<_> = generics
+        .params
+        .iter()
+        .filter_map(|param| match param {
+            syn::GenericParam::Type(param) => Some(&param.ident),
+            syn::GenericParam::Const(param) => Some(&param.ident),
+            syn::GenericParam::Lifetime(_) => None,
+        })
+        .collect();
+    let turbofish = if explicit_args.is_empty() {
+        quote! {}
+    } else {
+        quote! { ::<#(#explicit_args),*> }
+    };
+
+    // Type parameters that only appear in bounds still have to be used by the props struct
+    let type_params: Vec<_> = generics.type_params().map(|param| &param.ident).collect();
+    let phantom_field = if type_params.is_empty() {
+        None
+    } else {
+        Some(quote! {
+            #[builder(default, setter(skip))]
+            #[doc(hidden)]
+            __phantom: ::std::marker::PhantomData<(#(fn() -> #type_params,)*)>
+        })
+    };
+    let rest_of_props = phantom_field.as_ref().map(|_| quote! { .. });
+
     // Check if this is a no-parameter component (excluding slots)
     let is_no_params = params.is_empty();
 
+    // Wrappers return whatever the component returns, like a `Result` for fallible components
+    let output = &function.sig.output;
+
+    // Async components get async wrappers, which `html_async!` awaits
+    let asyncness = &function.sig.asyncness;
+    let await_internal = asyncness.map(|_| quote! { .await });
+
     // Generate wrapper functions
     let wrapper_fn = if has_slot {
         // Extract slot parameter name
-        let slot_param = &function.sig.inputs[0];
+        let slot_param = &internal_function.sig.inputs[0];
         let slot_param_name = if let syn::FnArg::Typed(pat_type) = slot_param {
             if let syn::Pat::Ident(ident) = &*pat_type.pat {
                 &ident.ident
@@ -218,9 +389,9 @@ pub fn component(_attr: TokenStream, item: TokenStream) -> TokenStream {
         // For all slot components (with or without params), use the same signature
         quote! {
             #[allow(non_snake_case)]
-            #vis fn #fn_name #impl_generics(#slot_param, props: #props_name #ty_generics) -> hypersynthetic::HtmlFragment #where_clause {
-                let #props_name { #(#param_names),* } = props;
-                #internal_fn_name(#slot_param_name, #(#param_names),*)
+            #vis #asyncness fn #fn_name #impl_generics(#slot_param, props: #props_name #ty_generics) #output #where_clause {
+                let #props_name { #(#param_names,)* #rest_of_props } = props;
+                #internal_fn_name #turbofish(#slot_param_name, #(#param_names),*)#await_internal
             }
         }
     } else if is_no_params {
@@ -229,34 +400,89 @@ pub fn component(_attr: TokenStream, item: TokenStream) -> TokenStream {
         quote! {
             // Props-based function (main interface for html! macro)
             #[allow(non_snake_case)]
-            #vis fn #fn_name #impl_generics(props: #props_name #ty_generics) -> hypersynthetic::HtmlFragment #where_clause {
-                let #props_name { #(#param_names),* } = props;
-                #internal_fn_name(#(#param_names),*)
+            #vis #asyncness fn #fn_name #impl_generics(props: #props_name #ty_generics) #output #where_clause {
+                let #props_name { #(#param_names,)* #rest_of_props } = props;
+                #internal_fn_name #turbofish(#(#param_names),*)#await_internal
             }
 
             // Direct callable function (for backwards compatibility)
             #[allow(non_snake_case)]
             #[doc(hidden)]
-            #vis fn #direct_fn_name #impl_generics() -> hypersynthetic::HtmlFragment #where_clause {
-                #internal_fn_name()
+            #vis #asyncness fn #direct_fn_name #impl_generics() #output #where_clause {
+                #internal_fn_name #turbofish()#await_internal
             }
         }
     } else {
         // For components with params, single props-based function
         quote! {
             #[allow(non_snake_case)]
-            #vis fn #fn_name #impl_generics(props: #props_name #ty_generics) -> hypersynthetic::HtmlFragment #where_clause {
-                let #props_name { #(#param_names),* } = props;
-                #internal_fn_name(#(#param_names),*)
+            #vis #asyncness fn #fn_name #impl_generics(props: #props_name #ty_generics) #output #where_clause {
+                let #props_name { #(#param_names,)* #rest_of_props } = props;
+                #internal_fn_name #turbofish(#(#param_names),*)#await_internal
             }
         }
     };
 
+    let rest_attrs_impl = if rest_attrs {
+        // The builder has the struct's generics followed by a tuple with the state of every
+        // field. The mutators of the rest field are there for any state of the other fields.
+        let mut builder_generics = generics.clone();
+        let field_states: Vec<TokenStream2> = params
+            .iter()
+            .enumerate()
+            .map(|(i, param)| {
+                if is_rest_attrs_param(param) {
+                    let ty = &param.ty;
+                    quote! { (#ty,) }
+                } else {
+                    let state = quote::format_ident!("TypedBuilderField{}", i);
+                    builder_generics.params.push(syn::parse_quote!(#state));
+                    quote! { #state }
+                }
+            })
+            .collect();
+        let (builder_impl_generics, _, _) = builder_generics.split_for_impl();
+        let generic_args = generics.params.iter().map(|param| match param {
+            syn::GenericParam::Lifetime(param) => {
+                let lifetime = &param.lifetime;
+                quote! { #lifetime }
+            }
+            syn::GenericParam::Type(param) => {
+                let ident = &param.ident;
+                quote! { #ident }
+            }
+            syn::GenericParam::Const(param) => {
+                let ident = &param.ident;
+                quote! { #ident }
+            }
+        });
+        quote! {
+            impl #builder_impl_generics hypersynthetic::component::AcceptsRestAttributes
+                for #props_builder_name<#(#generic_args,)* (#(#field_states,)*)> #where_clause
+            {
+                fn rest_attribute(
+                    self,
+                    name: &str,
+                    value: impl hypersynthetic::component::RestAttributeValue,
+                ) -> Self {
+                    self.add_rest_attribute(name, value)
+                }
+
+                fn rest_flag(self, name: &str) -> Self {
+                    self.add_rest_flag(name)
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+
     // Generate the final output - always generate Props struct
     let output = quote! {
         #[derive(::hypersynthetic::typed_builder_macro::TypedBuilder)]
         #vis struct #props_name #impl_generics #where_clause {
             #(#struct_fields,)*
+            #phantom_field
         }
 
         impl #impl_generics hypersynthetic::component::Props for #props_name #ty_generics #where_clause {
@@ -267,6 +493,8 @@ pub fn component(_attr: TokenStream, item: TokenStream) -> TokenStream {
             }
         }
 
+        #rest_attrs_impl
+
         #internal_function
 
         #wrapper_fn
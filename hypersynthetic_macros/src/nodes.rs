@@ -1,6 +1,6 @@
 use syn::{Expr, Ident, LitStr, Path};
 
-use crate::attributes::{Attribute, ForExpr, RegularAttribute};
+use crate::attributes::{Attribute, CaseExpr, ForExpr, InterpolatedSegment, RegularAttribute};
 
 #[derive(Clone)]
 pub enum NodeCollection {
@@ -8,11 +8,22 @@ pub enum NodeCollection {
 }
 
 #[derive(Clone)]
+#[allow(clippy::large_enum_variant)]
 pub enum Node {
+    /// Unquoted text between tags, with whitespace already collapsed.
+    BareText(LitStr),
+    /// `<!-- ... -->`, with a space around the content when there is any.
+    Comment(Vec<InterpolatedSegment>),
     Component(Component),
+    Conditional(Conditional),
     DocType,
     Element(Tag),
     Expression(Expr),
+    Fragment(Fragment),
+    Match(Match),
+    /// The content of a `<script>` or `<style>` element.
+    RawText(RawText),
+    Slot(Slot),
     Text(LitStr),
     UnescapedExpression(Expr),
 }
@@ -30,6 +41,107 @@ pub struct Component {
     pub name: Path,
     pub props: Vec<Attribute>,
     pub children: Vec<Node>,
+    pub slots: Vec<Slot>,
+}
+
+/// Content for a named slot of a component: `<:name>...</:name>`.
+#[derive(Clone)]
+pub struct Slot {
+    pub name: Ident,
+    pub children: Vec<Node>,
+}
+
+/// Text that is rendered without HTML escaping. Interpolated values are escaped for the
+/// element's language instead.
+#[derive(Clone)]
+pub struct RawText {
+    pub language: RawTextLanguage,
+    pub segments: Vec<RawSegment>,
+}
+
+#[derive(Clone)]
+pub enum RawSegment {
+    Str(LitStr),
+    /// `{expr}` inside a string literal of the script or stylesheet
+    InString(Expr),
+    /// `{{expr}}`, or `{expr}` outside the string literals of a single string literal content
+    Value(Expr),
+}
+
+#[derive(Clone, Copy)]
+pub enum RawTextLanguage {
+    JavaScript,
+    Css,
+}
+
+impl RawTextLanguage {
+    pub fn of_tag(tag_name: &str) -> Option<Self> {
+        if tag_name.eq_ignore_ascii_case("script") {
+            Some(RawTextLanguage::JavaScript)
+        } else if tag_name.eq_ignore_ascii_case("style") {
+            Some(RawTextLanguage::Css)
+        } else {
+            None
+        }
+    }
+}
+
+/// `<>...</>`, `<Fragment>...</Fragment>`, `<Provider value={expr}>...</Provider>` or
+/// `<ErrorBoundary fallback={expr}>...</ErrorBoundary>`. Only the children are rendered,
+/// and only the named forms can have pseudo-attributes.
+#[derive(Clone)]
+pub struct Fragment {
+    pub attributes: Vec<Attribute>,
+    pub children: Vec<Node>,
+    /// The value of a `<Provider>`, available to the children with `use_context`.
+    pub context: Option<Expr>,
+    /// The fallback of an `<ErrorBoundary>`, called with the error of a failed component.
+    pub fallback: Option<Expr>,
+}
+
+/// An `:if` element or component followed by its `:else-if` and `:else` siblings.
+/// The branch nodes have their conditional attributes removed.
+#[derive(Clone)]
+pub struct Conditional {
+    pub branches: Vec<(Expr, Node)>,
+    pub else_branch: Option<Box<Node>>,
+}
+
+/// `<template :match={expr}>` with one child per `:case`.
+/// The arm nodes have their `:case` attribute removed.
+#[derive(Clone)]
+pub struct Match {
+    pub expr: Expr,
+    pub arms: Vec<(CaseExpr, Node)>,
+}
+
+impl Node {
+    pub fn attributes(&self) -> &[Attribute] {
+        match self {
+            Node::Element(tag) => &tag.attributes,
+            Node::Component(component) => &component.props,
+            Node::Fragment(fragment) => &fragment.attributes,
+            _ => &[],
+        }
+    }
+
+    pub fn attributes_mut(&mut self) -> Option<&mut Vec<Attribute>> {
+        match self {
+            Node::Element(tag) => Some(&mut tag.attributes),
+            Node::Component(component) => Some(&mut component.props),
+            Node::Fragment(fragment) => Some(&mut fragment.attributes),
+            _ => None,
+        }
+    }
+
+    pub fn remove_attributes(&mut self, predicate: impl Fn(&Attribute) -> bool) {
+        match self {
+            Node::Element(tag) => tag.attributes.retain(|attr| !predicate(attr)),
+            Node::Component(component) => component.props.retain(|attr| !predicate(attr)),
+            Node::Fragment(fragment) => fragment.attributes.retain(|attr| !predicate(attr)),
+            _ => {}
+        }
+    }
 }
 
 impl Tag {
@@ -129,3 +241,19 @@ impl Component {
         }
     }
 }
+
+impl Fragment {
+    pub fn get_for_attribute(&self) -> Option<ForExpr> {
+        self.attributes.iter().find_map(|attr| match attr {
+            Attribute::For(for_expr) => Some(for_expr.clone()),
+            _ => None,
+        })
+    }
+
+    pub fn get_if_attribute(&self) -> Option<Expr> {
+        self.attributes.iter().find_map(|attr| match attr {
+            Attribute::If(expr) => Some(expr.clone()),
+            _ => None,
+        })
+    }
+}
@@ -1,13 +1,22 @@
-use proc_macro2::Span;
+use proc_macro2::{Delimiter, LineColumn, Spacing, Span, TokenTree};
+use quote::ToTokens as _;
 use syn::{
     Expr, Ident, LitBool, LitStr, Pat, Path, Result, Token, braced,
+    buffer::Cursor,
     parse::{Parse, ParseStream},
+    spanned::Spanned,
     token::Brace,
 };
 
 use crate::{
-    attributes::{AttrName, AttrValue, Attribute, ForExpr, InterpolatedSegment, RegularAttribute},
-    nodes::{Component, Node, NodeCollection, Tag},
+    attributes::{
+        AttrName, AttrValue, Attribute, CaseExpr, ClassDirective, ForExpr, InterpolatedSegment,
+        LetBinding, RegularAttribute, StyleDirective,
+    },
+    nodes::{
+        Component, Conditional, Fragment, Match, Node, NodeCollection, RawSegment, RawText,
+        RawTextLanguage, Slot, Tag,
+    },
     utils::{extract_ident_from_path, is_path_pascal_case, path_to_string},
 };
 
@@ -17,6 +26,12 @@ impl Parse for Node {
             let _: Token![<] = input.parse()?;
             let _: Token![!] = input.parse()?;
 
+            if input.peek(Token![-]) && input.peek2(Token![-]) {
+                let _: Token![-] = input.parse()?;
+                let _: Token![-] = input.parse()?;
+                return parse_comment(input);
+            }
+
             let lookahead = input.lookahead1();
             if lookahead.peek(Ident) {
                 let ident: Ident = input.parse()?;
@@ -30,6 +45,50 @@ impl Parse for Node {
             }
         }
 
+        if input.peek(Token![<]) && input.peek2(Token![>]) {
+            let _: Token![<] = input.parse()?;
+            let end_of_opening_tag: Token![>] = input.parse()?;
+            let children = parse_children(input, Some(end_of_opening_tag.span.end()))?;
+            let children = group_siblings(children)?;
+
+            if !(input.peek(Token![<]) && input.peek2(Token![/]) && input.peek3(Token![>])) {
+                return Err(input.error("Expected closing tag </>"));
+            }
+            let _: Token![<] = input.parse()?;
+            let _: Token![/] = input.parse()?;
+            let _: Token![>] = input.parse()?;
+
+            return Ok(Node::Fragment(Fragment {
+                attributes: Vec::new(),
+                children,
+                context: None,
+                fallback: None,
+            }));
+        }
+
+        if input.peek(Token![<]) && input.peek2(Token![:]) {
+            let _: Token![<] = input.parse()?;
+            let _: Token![:] = input.parse()?;
+            let name: Ident = input.parse()?;
+            let end_of_opening_tag: Token![>] = input.parse()?;
+            let children = parse_children(input, Some(end_of_opening_tag.span.end()))?;
+            let children = group_siblings(children)?;
+
+            let _: Token![<] = input.parse()?;
+            let _: Token![/] = input.parse()?;
+            let _: Token![:] = input.parse()?;
+            let closing_name: Ident = input.parse()?;
+            if closing_name != name {
+                return Err(syn::Error::new(
+                    closing_name.span(),
+                    format!("Expected closing tag </:{name}>, found </:{closing_name}>"),
+                ));
+            }
+            let _: Token![>] = input.parse()?;
+
+            return Ok(Node::Slot(Slot { name, children }));
+        }
+
         if input.peek(Token![<]) {
             let _: Token![<] = input.parse()?;
             let tag_name: Path = input.parse()?;
@@ -50,19 +109,93 @@ impl Parse for Node {
                 end_of_tag = end_of_regular_tag || end_of_self_closing_tag;
             }
 
-            let is_component = is_path_pascal_case(&tag_name);
+            let context = if tag_name.is_ident("Provider") {
+                Some(expression_attribute(&tag_name, &mut attributes, "value")?)
+            } else {
+                None
+            };
+            let fallback = if tag_name.is_ident("ErrorBoundary") {
+                Some(expression_attribute(
+                    &tag_name,
+                    &mut attributes,
+                    "fallback",
+                )?)
+            } else {
+                None
+            };
+            let is_fragment =
+                context.is_some() || fallback.is_some() || tag_name.is_ident("Fragment");
+            if is_fragment
+                && let Some(Attribute::RegularAttribute(attr)) = attributes
+                    .iter()
+                    .find(|attr| matches!(attr, Attribute::RegularAttribute(_)))
+            {
+                let span = match &attr.name {
+                    AttrName::Literal(name) => name.span(),
+                    AttrName::Expression(expr) => expr.span(),
+                };
+                let message = if context.is_some() {
+                    "`<Provider>` only accepts `value` and pseudo-attributes like `:if` and `:for`"
+                } else if fallback.is_some() {
+                    "`<ErrorBoundary>` only accepts `fallback` and pseudo-attributes like `:if` and `:for`"
+                } else {
+                    "`<Fragment>` only accepts pseudo-attributes like `:if` and `:for`"
+                };
+                return Err(syn::Error::new(span, message));
+            }
+            let is_component = !is_fragment && is_path_pascal_case(&tag_name);
+            if is_component
+                && let Some(directive) = attributes.iter().find_map(|attr| match attr {
+                    Attribute::Class(class) => Some(class.name.span()),
+                    Attribute::Style(style) => Some(style.property.span()),
+                    Attribute::Spread(expr) => Some(expr.span()),
+                    _ => None,
+                })
+            {
+                return Err(syn::Error::new(
+                    directive,
+                    "`class:` and `style:` directives and `{..spread}` attributes can only be used on elements",
+                ));
+            }
+            let match_expr = if !is_component && tag_name.is_ident("template") {
+                match_attribute(&attributes)?
+            } else if let Some(Attribute::Match(expr)) = attributes
+                .iter()
+                .find(|attr| matches!(attr, Attribute::Match(_)))
+            {
+                return Err(syn::Error::new(
+                    expr.span(),
+                    "`:match` can only be used on `<template>`",
+                ));
+            } else {
+                None
+            };
 
             // Self-closing tag
             if input.peek(Token![/]) && input.peek2(Token![>]) {
                 let _: Token![/] = input.parse()?;
                 let _: Token![>] = input.parse()?;
 
+                if match_expr.is_some() {
+                    return Err(input.error("`<template :match>` must contain `:case` children"));
+                }
+
+                if is_fragment {
+                    return Ok(Node::Fragment(Fragment {
+                        attributes,
+                        children: Vec::new(),
+                        context,
+                        fallback,
+                    }));
+                }
+
                 // Self-closing -> no children (slots)
                 if is_component {
                     return Ok(Node::Component(Component {
                         name: tag_name,
                         props: attributes,
                         children: Vec::new(),
+                        slots: Vec::new(),
                     }));
                 }
 
@@ -74,16 +207,28 @@ impl Parse for Node {
                 }));
             }
 
-            let _: Token![>] = input.parse()?;
+            let end_of_opening_tag: Token![>] = input.parse()?;
 
-            let mut children: Vec<Node> = Vec::new();
-            while input.peek(Token![<]) && input.peek2(Ident)
-                || input.peek(LitStr)
-                || input.peek(Brace)
-            {
-                let child: Node = input.parse()?;
-                children.push(child);
+            let raw_text_language = if is_component || is_fragment {
+                None
+            } else {
+                RawTextLanguage::of_tag(&path_to_string(&tag_name))
+            };
+            let children = match raw_text_language {
+                Some(language) => parse_raw_text(input, language)?,
+                None => parse_children(input, Some(end_of_opening_tag.span.end()))?,
+            };
+            if let Some(expr) = match_expr {
+                let arms = match_arms(children)?;
+                parse_closing_tag(input, &tag_name)?;
+                return Ok(Node::Match(Match { expr, arms }));
             }
+            let (children, slots) = if is_component {
+                split_slots(children)?
+            } else {
+                (children, Vec::new())
+            };
+            let children = group_siblings(children)?;
 
             let element = Tag {
                 tag_name: extract_ident_from_path(&tag_name),
@@ -94,30 +239,32 @@ impl Parse for Node {
 
             // Check for the closing tag
             if input.peek(Token![<]) && input.peek2(Token![/]) && input.peek3(Ident) {
-                let _: Token![<] = input.parse()?;
-                let _: Token![/] = input.parse()?;
-                let closing_tag_name: Path = input.parse()?;
-                if closing_tag_name != tag_name {
-                    Err(input.error(format!(
-                        "Expected closing tag {}, found {}",
-                        path_to_string(&tag_name),
-                        path_to_string(&closing_tag_name)
-                    )))
-                } else {
-                    let _: Token![>] = input.parse()?;
-
-                    if is_component {
-                        return Ok(Node::Component(Component {
-                            name: tag_name,
-                            props: attributes,
-                            children,
-                        }));
-                    }
+                parse_closing_tag(input, &tag_name)?;
 
-                    Ok(Node::Element(element))
+                if is_fragment {
+                    return Ok(Node::Fragment(Fragment {
+                        attributes,
+                        children,
+                        context,
+                        fallback,
+                    }));
                 }
+
+                if is_component {
+                    return Ok(Node::Component(Component {
+                        name: tag_name,
+                        props: attributes,
+                        children,
+                        slots,
+                    }));
+                }
+
+                Ok(Node::Element(element))
             } else {
-                Ok(Node::Text(input.parse()?))
+                Err(input.error(format!(
+                    "Expected closing tag </{}>",
+                    path_to_string(&tag_name)
+                )))
             }
         } else if input.peek(LitStr) {
             let content: LitStr = input.parse()?;
@@ -147,6 +294,36 @@ impl Parse for Node {
 impl Parse for Attribute {
     fn parse(input: ParseStream) -> Result<Self> {
         if input.peek(Token![:]) {
+            if input.peek2(Token![match]) {
+                let _: Token![:] = input.parse()?;
+                let _: Token![match] = input.parse()?;
+                let _: Token![=] = input.parse()?;
+
+                let content;
+                braced!(content in input);
+                return Ok(Attribute::Match(content.parse()?));
+            } else if peek_pseudo_attribute(input, "case") {
+                let _: Token![:] = input.parse()?;
+                let _: Ident = input.parse()?;
+                let _: Token![=] = input.parse()?;
+
+                let content;
+                braced!(content in input);
+                return Ok(Attribute::Case(content.parse()?));
+            }
+            if peek_empty_attribute(input) {
+                let _: Token![:] = input.parse()?;
+                return Ok(Attribute::Empty(input.parse()?));
+            }
+            if input.peek2(Token![let]) {
+                let _: Token![:] = input.parse()?;
+                let _: Token![let] = input.parse()?;
+                let _: Token![=] = input.parse()?;
+
+                let content;
+                braced!(content in input);
+                return Ok(Attribute::Let(content.parse()?));
+            }
             if input.peek2(Token![for]) {
                 let _: Token![:] = input.parse()?;
                 let _: Token![for] = input.parse()?;
@@ -163,10 +340,69 @@ impl Parse for Attribute {
                 let content;
                 braced!(content in input);
                 return Ok(Attribute::If(content.parse()?));
+            } else if input.peek2(Token![else]) {
+                let _: Token![:] = input.parse()?;
+                let else_token: Token![else] = input.parse()?;
+                if input.peek(Token![-]) && input.peek2(Token![if]) {
+                    let _: Token![-] = input.parse()?;
+                    let _: Token![if] = input.parse()?;
+                    let _: Token![=] = input.parse()?;
+
+                    let content;
+                    braced!(content in input);
+                    return Ok(Attribute::ElseIf(content.parse()?));
+                }
+                return Ok(Attribute::Else(else_token));
+            }
+        }
+        if input.peek(Brace) {
+            let fork = input.fork();
+            let content;
+            braced!(content in fork);
+            if content.peek(Token![..]) {
+                let content;
+                braced!(content in input);
+                let _: Token![..] = content.parse()?;
+                return Ok(Attribute::Spread(content.parse()?));
             }
         }
+
         let name: AttrName = input.parse()?;
 
+        if let AttrName::Literal(lit) = &name
+            && let Some(class_name) = lit.value().strip_prefix("class:")
+        {
+            let _: Token![=] = input.parse()?;
+            let content;
+            braced!(content in input);
+            return Ok(Attribute::Class(ClassDirective {
+                name: LitStr::new(class_name, lit.span()),
+                condition: content.parse()?,
+            }));
+        }
+
+        if let AttrName::Literal(lit) = &name
+            && let Some(property) = lit.value().strip_prefix("style:")
+        {
+            let _: Token![=] = input.parse()?;
+            return Ok(Attribute::Style(StyleDirective {
+                property: LitStr::new(property, lit.span()),
+                value: input.parse()?,
+            }));
+        }
+
+        if let AttrName::Literal(lit) = &name
+            && let Some(toggled_name) = lit.value().strip_suffix(":if")
+        {
+            let _: Token![=] = input.parse()?;
+            let content;
+            braced!(content in input);
+            return Ok(Attribute::RegularAttribute(RegularAttribute {
+                name: AttrName::Literal(LitStr::new(toggled_name, lit.span())),
+                value: Some(AttrValue::Toggle(content.parse()?)),
+            }));
+        }
+
         // If the next token is '=', then expect a value. Otherwise, no value.
         let value = if input.peek(Token![=]) {
             let _: Token![=] = input.parse()?;
@@ -182,6 +418,23 @@ impl Parse for Attribute {
     }
 }
 
+/// Checks for `:name` where `name` is not a Rust keyword.
+fn peek_pseudo_attribute(input: ParseStream, name: &str) -> bool {
+    let fork = input.fork();
+    fork.parse::<Token![:]>().is_ok()
+        && fork.parse::<Ident>().is_ok_and(|ident| ident == name)
+        && fork.peek(Token![=])
+}
+
+/// `:empty` takes no value.
+fn peek_empty_attribute(input: ParseStream) -> bool {
+    let fork = input.fork();
+    fork.parse::<Token![:]>().is_ok()
+        && fork.parse::<Ident>().is_ok_and(|ident| ident == "empty")
+        && !fork.peek(Token![=])
+        && !fork.peek(Token![-])
+}
+
 macro_rules! match_keyword {
     ($input:expr, $keyword:ident, $name:expr, $saw_word:expr) => {
         if $input.peek(Token![$keyword]) {
@@ -336,17 +589,839 @@ impl Parse for ForExpr {
         let pat: Pat = Pat::parse_single(input)?;
         let _: Token![in] = input.parse()?;
         let collection: Expr = input.parse()?;
-        Ok(ForExpr { pat, collection })
+        let info = if input.peek(Token![,]) {
+            let _: Token![,] = input.parse()?;
+            Some(input.parse()?)
+        } else {
+            None
+        };
+        Ok(ForExpr {
+            pat,
+            collection,
+            info,
+            empty: None,
+        })
+    }
+}
+
+impl Parse for LetBinding {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut pat: Pat = Pat::parse_single(input)?;
+        if input.peek(Token![:]) {
+            pat = Pat::Type(syn::PatType {
+                attrs: Vec::new(),
+                pat: Box::new(pat),
+                colon_token: input.parse()?,
+                ty: input.parse()?,
+            });
+        }
+        let _: Token![=] = input.parse()?;
+        let expr: Expr = input.parse()?;
+        Ok(LetBinding { pat, expr })
+    }
+}
+
+impl Parse for CaseExpr {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let pat: Pat = Pat::parse_multi_with_leading_vert(input)?;
+        let guard = if input.peek(Token![if]) {
+            let _: Token![if] = input.parse()?;
+            Some(input.parse()?)
+        } else {
+            None
+        };
+        Ok(CaseExpr { pat, guard })
     }
 }
 
 impl Parse for NodeCollection {
     fn parse(input: ParseStream) -> Result<Self> {
-        let mut nodes = Vec::new();
-        while !input.is_empty() {
-            nodes.push(input.parse()?);
+        let nodes = parse_children(input, None)?;
+        if !input.is_empty() {
+            return Err(input.error("Unexpected closing tag"));
+        }
+        Ok(NodeCollection::Nodes(group_siblings(nodes)?))
+    }
+}
+
+/// Parses nodes until a closing tag or the end of input.
+/// `prev_end` is where the token before the first child ends, if there is one.
+fn parse_children(input: ParseStream, mut prev_end: Option<LineColumn>) -> Result<Vec<Node>> {
+    let mut children = Vec::new();
+    while !(input.is_empty() || input.peek(Token![<]) && input.peek2(Token![/])) {
+        let start = input.cursor();
+        let child = if input.peek(Token![<]) || input.peek(LitStr) || input.peek(Brace) {
+            input.parse()?
+        } else {
+            parse_bare_text(input, prev_end)?
+        };
+        prev_end = last_token_end(start, input.cursor());
+        children.push(child);
+    }
+    Ok(children)
+}
+
+/// Parses a run of tokens up to the next tag, string literal or expression as text.
+///
+/// Whitespace is reconstructed from token positions and collapsed the way JSX does it:
+/// any whitespace inside the run becomes a single space, whitespace at the edges of the run
+/// is kept as a single space only when it doesn't contain a line break.
+fn parse_bare_text(input: ParseStream, prev_end: Option<LineColumn>) -> Result<Node> {
+    let span = input.span();
+    let mut text = String::new();
+    if prev_end.is_some_and(|end| is_inline_gap(end, span.start())) {
+        text.push(' ');
+    }
+
+    let mut last: Option<TokenTree> = None;
+    while !input.is_empty() && !input.peek(Token![<]) && !input.peek(LitStr) && !input.peek(Brace) {
+        let token: TokenTree = input.parse()?;
+        push_token_text(&mut text, last.as_ref(), &token);
+        last = Some(token);
+    }
+
+    if let Some(last) = last
+        && !input.is_empty()
+        && is_inline_gap(last.span().end(), input.span().start())
+    {
+        text.push(' ');
+    }
+
+    Ok(Node::BareText(LitStr::new(&text, span)))
+}
+
+fn push_token_text(text: &mut String, prev: Option<&TokenTree>, token: &TokenTree) {
+    if let Some(prev) = prev
+        && has_gap(prev, token.span())
+    {
+        text.push(' ');
+    }
+
+    match token {
+        TokenTree::Group(group) => {
+            let (open, close) = match group.delimiter() {
+                Delimiter::Parenthesis => ("(", ")"),
+                Delimiter::Bracket => ("[", "]"),
+                Delimiter::Brace => ("{", "}"),
+                Delimiter::None => ("", ""),
+            };
+            text.push_str(open);
+            let mut inner_prev: Option<TokenTree> = None;
+            for inner in group.stream() {
+                let gap_after_open = inner_prev.is_none()
+                    && !open.is_empty()
+                    && group.span_open().end() != inner.span().start();
+                if gap_after_open && has_locations(inner.span()) {
+                    text.push(' ');
+                }
+                push_token_text(text, inner_prev.as_ref(), &inner);
+                inner_prev = Some(inner);
+            }
+            if let Some(inner_prev) = inner_prev
+                && !close.is_empty()
+                && has_locations(inner_prev.span())
+                && inner_prev.span().end() != group.span_close().start()
+            {
+                text.push(' ');
+            }
+            text.push_str(close);
+        }
+        TokenTree::Ident(ident) => text.push_str(&ident.to_string()),
+        TokenTree::Punct(punct) => text.push(punct.as_char()),
+        TokenTree::Literal(literal) => text.push_str(&literal.to_string()),
+    }
+}
+
+/// Whether there was whitespace between two adjacent tokens in the source.
+/// Without location information (older compilers), only joint punctuation is glued together.
+fn has_gap(prev: &TokenTree, next: Span) -> bool {
+    if has_locations(next) {
+        prev.span().end() != next.start()
+    } else {
+        !matches!(prev, TokenTree::Punct(punct) if punct.spacing() == Spacing::Joint)
+    }
+}
+
+fn is_inline_gap(prev_end: LineColumn, next_start: LineColumn) -> bool {
+    prev_end.line == next_start.line && prev_end.column < next_start.column
+}
+
+fn has_locations(span: Span) -> bool {
+    span.start().line != 0
+}
+
+fn last_token_end(mut from: Cursor, to: Cursor) -> Option<LineColumn> {
+    let mut end = None;
+    while from != to {
+        let (token, next) = from.token_tree()?;
+        end = Some(token.span().end());
+        from = next;
+    }
+    end
+}
+
+/// Parses the content of a comment after `<!--`, up to and including `-->`.
+///
+/// String literals are text and `{expr}` is interpolated. Other tokens are text as well,
+/// like in [parse_bare_text]. Whitespace between the parts becomes a single space.
+fn parse_comment(input: ParseStream) -> Result<Node> {
+    let is_end = |input: ParseStream| {
+        input.peek(Token![-]) && input.peek2(Token![-]) && input.peek3(Token![>])
+    };
+    let mut text = " ".to_owned();
+    let mut segments = Vec::new();
+    let mut prev: Option<TokenTree> = None;
+    while !is_end(input) {
+        if input.is_empty() {
+            return Err(input.error("Expected `-->` at the end of the comment"));
+        }
+        let token: TokenTree = input.parse()?;
+        if prev
+            .as_ref()
+            .is_some_and(|prev| has_gap(prev, token.span()))
+        {
+            text.push(' ');
+        }
+        match &token {
+            TokenTree::Group(group) if group.delimiter() == Delimiter::Brace => {
+                let expr: Expr = syn::parse2(group.stream())?;
+                if !text.is_empty() {
+                    segments.push(InterpolatedSegment::Str(LitStr::new(&text, token.span())));
+                    text.clear();
+                }
+                segments.push(InterpolatedSegment::Expr(expr));
+            }
+            TokenTree::Literal(literal) => match syn::Lit::new(literal.clone()) {
+                syn::Lit::Str(lit) => text.push_str(&lit.value()),
+                _ => push_token_text(&mut text, None, &token),
+            },
+            _ => push_token_text(&mut text, None, &token),
+        }
+        prev = Some(token);
+    }
+    let _: Token![-] = input.parse()?;
+    let _: Token![-] = input.parse()?;
+    let _: Token![>] = input.parse()?;
+
+    if prev.is_none() {
+        return Ok(Node::Comment(Vec::new()));
+    }
+    text.push(' ');
+    segments.push(InterpolatedSegment::Str(LitStr::new(
+        &text,
+        Span::call_site(),
+    )));
+    Ok(Node::Comment(segments))
+}
+
+/// Parses the content of a `<script>` or `<style>` element up to its closing tag as raw text.
+///
+/// The text is reconstructed from token positions: a gap between two tokens becomes a line break
+/// when they are on different lines and a space otherwise. `{expr}` is interpolated inside string
+/// literals, and `{{expr}}` anywhere else, since single braces outside of string literals belong
+/// to the script or the stylesheet.
+/// When the content is a single string literal, its value is the text, which allows code that
+/// Rust can't tokenize, like single-quoted strings. `{expr}` is interpolated anywhere there,
+/// and the text is scanned for quotes to tell whether it is inside a string literal.
+fn parse_raw_text(input: ParseStream, language: RawTextLanguage) -> Result<Vec<Node>> {
+    let is_end =
+        |input: ParseStream| input.is_empty() || input.peek(Token![<]) && input.peek2(Token![/]);
+    let mut text = String::new();
+    let mut segments = Vec::new();
+
+    let fork = input.fork();
+    if fork.parse::<LitStr>().is_ok() && is_end(&fork) {
+        let content: LitStr = input.parse()?;
+        let mut scanner = RawTextScanner::new(language);
+        push_raw_interpolated(
+            &mut text,
+            &mut segments,
+            &content.value(),
+            Some(&mut scanner),
+            content.span(),
+        )?;
+    } else {
+        let mut prev: Option<TokenTree> = None;
+        while !is_end(input) {
+            let token: TokenTree = input.parse()?;
+            if let Some(prev) = &prev {
+                push_raw_gap(&mut text, prev.span().end(), prev, token.span().start());
+            }
+            push_raw_token(&mut text, &mut segments, &token)?;
+            prev = Some(token);
+        }
+    }
+
+    if !text.is_empty() {
+        segments.push(RawSegment::Str(LitStr::new(&text, Span::call_site())));
+    }
+    if segments.is_empty() {
+        return Ok(Vec::new());
+    }
+    Ok(vec![Node::RawText(RawText { language, segments })])
+}
+
+fn push_raw_token(
+    text: &mut String,
+    segments: &mut Vec<RawSegment>,
+    token: &TokenTree,
+) -> Result<()> {
+    match token {
+        TokenTree::Group(group) => {
+            if group.delimiter() == Delimiter::Brace {
+                let mut inner = group.stream().into_iter();
+                match (inner.next(), inner.next()) {
+                    (Some(TokenTree::Group(value)), None)
+                        if value.delimiter() == Delimiter::Brace =>
+                    {
+                        let expr: Expr = syn::parse2(value.stream()).map_err(|err| {
+                            syn::Error::new(
+                                group.span(),
+                                format!("Invalid interpolation `{{{group}}}`: {err}"),
+                            )
+                        })?;
+                        push_raw_segment(text, segments, RawSegment::Value(expr), group.span());
+                        return Ok(());
+                    }
+                    // `{name}` is an object in JavaScript, but more likely meant as a value
+                    (Some(TokenTree::Ident(ident)), None)
+                        if syn::parse2::<Ident>(ident.to_token_stream()).is_ok() =>
+                    {
+                        return Err(syn::Error::new(
+                            group.span(),
+                            format!(
+                                "`{{{ident}}}` would be written out as is. Use `{{{{{ident}}}}}` \
+                                 to insert the value of `{ident}`, or `{{{ident}: {ident}}}` \
+                                 for an object"
+                            ),
+                        ));
+                    }
+                    _ => {}
+                }
+            }
+            let (open, close) = match group.delimiter() {
+                Delimiter::Parenthesis => ("(", ")"),
+                Delimiter::Bracket => ("[", "]"),
+                Delimiter::Brace => ("{", "}"),
+                Delimiter::None => ("", ""),
+            };
+            text.push_str(open);
+            let mut prev: Option<TokenTree> = None;
+            for inner in group.stream() {
+                match &prev {
+                    Some(prev) => push_raw_gap(text, prev.span().end(), prev, inner.span().start()),
+                    None if !open.is_empty() && has_locations(inner.span()) => {
+                        push_raw_line_gap(text, group.span_open().end(), inner.span().start())
+                    }
+                    None => {}
+                }
+                push_raw_token(text, segments, &inner)?;
+                prev = Some(inner);
+            }
+            if let Some(prev) = prev
+                && !close.is_empty()
+                && has_locations(prev.span())
+            {
+                push_raw_line_gap(text, prev.span().end(), group.span_close().start());
+            }
+            text.push_str(close);
+        }
+        TokenTree::Ident(ident) => text.push_str(&ident.to_string()),
+        TokenTree::Punct(punct) => text.push(punct.as_char()),
+        TokenTree::Literal(literal) => {
+            let source = literal.to_string();
+            match source.strip_prefix('"').and_then(|s| s.strip_suffix('"')) {
+                Some(content) => {
+                    text.push('"');
+                    push_raw_interpolated(text, segments, content, None, literal.span())?;
+                    text.push('"');
+                }
+                None => text.push_str(&source),
+            }
         }
-        Ok(NodeCollection::Nodes(nodes))
+    }
+    Ok(())
+}
+
+/// Adds the whitespace between two tokens of raw text.
+/// Without location information (older compilers), only joint punctuation is glued together.
+fn push_raw_gap(text: &mut String, prev_end: LineColumn, prev: &TokenTree, next_start: LineColumn) {
+    if has_locations(prev.span()) {
+        push_raw_line_gap(text, prev_end, next_start);
+    } else if !matches!(prev, TokenTree::Punct(punct) if punct.spacing() == Spacing::Joint) {
+        text.push(' ');
+    }
+}
+
+fn push_raw_line_gap(text: &mut String, prev_end: LineColumn, next_start: LineColumn) {
+    if prev_end.line < next_start.line {
+        text.push('\n');
+    } else if prev_end.column < next_start.column {
+        text.push(' ');
+    }
+}
+
+fn push_raw_segment(
+    text: &mut String,
+    segments: &mut Vec<RawSegment>,
+    segment: RawSegment,
+    span: Span,
+) {
+    if !text.is_empty() {
+        segments.push(RawSegment::Str(LitStr::new(text, span)));
+        text.clear();
+    }
+    segments.push(segment);
+}
+
+/// Splits `{expr}` out of a string. `{{` and `}}` stand for literal braces.
+///
+/// Without a `scanner`, the string is the content of a string literal of the script as written,
+/// where escape sequences like `\u{..}` are copied as they are. With a `scanner`, it is the
+/// whole content of the element.
+fn push_raw_interpolated(
+    text: &mut String,
+    segments: &mut Vec<RawSegment>,
+    s: &str,
+    mut scanner: Option<&mut RawTextScanner>,
+    span: Span,
+) -> Result<()> {
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        let c = match c {
+            '\\' if scanner.is_none() => {
+                text.push(c);
+                let Some(escaped) = chars.next() else {
+                    break;
+                };
+                text.push(escaped);
+                if escaped == 'u' && chars.peek() == Some(&'{') {
+                    for c in chars.by_ref() {
+                        text.push(c);
+                        if c == '}' {
+                            break;
+                        }
+                    }
+                }
+                continue;
+            }
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                '{'
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                '}'
+            }
+            '{' => {
+                let expr: String = chars.by_ref().take_while(|&c| c != '}').collect();
+                let parsed: Expr = syn::parse_str(&expr).map_err(|err| {
+                    syn::Error::new(span, format!("Invalid interpolation `{{{expr}}}`: {err}"))
+                })?;
+                let segment = match &scanner {
+                    None => RawSegment::InString(parsed),
+                    Some(scanner) if scanner.in_comment() => {
+                        return Err(syn::Error::new(
+                            span,
+                            format!("`{{{expr}}}` can't be interpolated into a comment"),
+                        ));
+                    }
+                    Some(scanner) if scanner.in_string() => RawSegment::InString(parsed),
+                    Some(_) => RawSegment::Value(parsed),
+                };
+                push_raw_segment(text, segments, segment, span);
+                continue;
+            }
+            c => c,
+        };
+        if let Some(scanner) = &mut scanner {
+            scanner.scan(c);
+        }
+        text.push(c);
+    }
+    Ok(())
+}
+
+/// Follows string literals and comments through the text of a `<script>` or `<style>` element.
+struct RawTextScanner {
+    language: RawTextLanguage,
+    /// The quote of the string literal the text is in
+    quote: Option<char>,
+    /// Whether the text is in a `/* */` comment, or in a `//` comment when `Some(false)`
+    comment: Option<bool>,
+    escaped: bool,
+    prev: Option<char>,
+}
+
+impl RawTextScanner {
+    fn new(language: RawTextLanguage) -> Self {
+        RawTextScanner {
+            language,
+            quote: None,
+            comment: None,
+            escaped: false,
+            prev: None,
+        }
+    }
+
+    fn in_string(&self) -> bool {
+        self.quote.is_some()
+    }
+
+    fn in_comment(&self) -> bool {
+        self.comment.is_some()
+    }
+
+    fn scan(&mut self, c: char) {
+        let prev = self.prev.replace(c);
+        let is_js = matches!(self.language, RawTextLanguage::JavaScript);
+        if let Some(block) = self.comment {
+            if block && prev == Some('*') && c == '/' || !block && c == '\n' {
+                self.comment = None;
+                self.prev = None;
+            }
+        } else if let Some(quote) = self.quote {
+            if self.escaped {
+                self.escaped = false;
+            } else if c == '\\' {
+                self.escaped = true;
+            } else if c == quote {
+                self.quote = None;
+            }
+        } else {
+            match (prev, c) {
+                (Some('/'), '*') => {
+                    self.comment = Some(true);
+                    // The `*` of `/*` doesn't start the `*/` that ends the comment
+                    self.prev = None;
+                }
+                (Some('/'), '/') if is_js => self.comment = Some(false),
+                (_, '"' | '\'') => self.quote = Some(c),
+                (_, '`') if is_js => self.quote = Some(c),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Takes a `name={expression}` attribute out of a built-in tag like `<Provider>`.
+fn expression_attribute(
+    tag_name: &Path,
+    attributes: &mut Vec<Attribute>,
+    name: &str,
+) -> Result<Expr> {
+    let tag = path_to_string(tag_name);
+    let position = attributes.iter().position(|attr| {
+        matches!(
+            attr,
+            Attribute::RegularAttribute(RegularAttribute {
+                name: AttrName::Literal(attr_name),
+                ..
+            }) if attr_name.value() == name
+        )
+    });
+    let Some(position) = position else {
+        return Err(syn::Error::new(
+            tag_name.span(),
+            format!("`<{tag}>` needs a `{name}` attribute"),
+        ));
+    };
+    let Attribute::RegularAttribute(attr) = attributes.remove(position) else {
+        unreachable!()
+    };
+    match attr.value {
+        Some(AttrValue::Expression(expr)) => Ok(expr),
+        Some(AttrValue::Literal(lit)) => Ok(syn::parse_quote!(#lit)),
+        _ => Err(syn::Error::new(
+            tag_name.span(),
+            format!("`<{tag}>` expects `{name}={{expression}}`"),
+        )),
+    }
+}
+
+fn parse_closing_tag(input: ParseStream, tag_name: &Path) -> Result<()> {
+    let _: Token![<] = input.parse()?;
+    let _: Token![/] = input.parse()?;
+    let closing_tag_name: Path = input.parse()?;
+    // Generic arguments like in `<Table::<Row>>` don't need to be repeated in the closing tag
+    let same_tag = closing_tag_name.segments.len() == tag_name.segments.len()
+        && closing_tag_name
+            .segments
+            .iter()
+            .zip(&tag_name.segments)
+            .all(|(closing, opening)| {
+                closing.ident == opening.ident
+                    && (closing.arguments.is_none() || closing.arguments == opening.arguments)
+            });
+    if !same_tag {
+        return Err(input.error(format!(
+            "Expected closing tag {}, found {}",
+            path_to_string(tag_name),
+            path_to_string(&closing_tag_name)
+        )));
+    }
+    let _: Token![>] = input.parse()?;
+    Ok(())
+}
+
+/// Separates the content of named slots from the children of a component. A slot is filled
+/// either with `<:name>...</:name>` or with an element that has a `slot="name"` attribute.
+fn split_slots(children: Vec<Node>) -> Result<(Vec<Node>, Vec<Slot>)> {
+    let mut default_children = Vec::new();
+    let mut slots: Vec<Slot> = Vec::new();
+    let mut add_to_slot = |name: Ident, mut children: Vec<Node>| match slots
+        .iter_mut()
+        .find(|slot| slot.name == name)
+    {
+        Some(slot) => slot.children.append(&mut children),
+        None => slots.push(Slot { name, children }),
+    };
+
+    for child in children {
+        match child {
+            Node::Slot(slot) => add_to_slot(slot.name, slot.children),
+            Node::Element(mut tag) => match slot_attribute(&tag)? {
+                Some(name) => {
+                    tag.attributes.retain(|attr| !is_slot_attribute(attr));
+                    add_to_slot(name, vec![Node::Element(tag)]);
+                }
+                None => default_children.push(Node::Element(tag)),
+            },
+            child => default_children.push(child),
+        }
+    }
+
+    Ok((default_children, slots))
+}
+
+fn is_slot_attribute(attr: &Attribute) -> bool {
+    matches!(
+        attr,
+        Attribute::RegularAttribute(RegularAttribute {
+            name: AttrName::Literal(name),
+            ..
+        }) if name.value() == "slot"
+    )
+}
+
+fn slot_attribute(tag: &Tag) -> Result<Option<Ident>> {
+    let Some(Attribute::RegularAttribute(attr)) =
+        tag.attributes.iter().find(|attr| is_slot_attribute(attr))
+    else {
+        return Ok(None);
+    };
+    match &attr.value {
+        Some(AttrValue::Literal(value)) => value.parse().map(Some),
+        _ => Err(syn::Error::new(
+            tag.tag_name.span(),
+            "`slot` attribute on a child of a component must be a string literal with the slot name",
+        )),
+    }
+}
+
+/// Post-processes a list of sibling nodes once all of them are parsed.
+fn group_siblings(nodes: Vec<Node>) -> Result<Vec<Node>> {
+    for node in &nodes {
+        if let Node::Slot(slot) = node {
+            return Err(syn::Error::new(
+                slot.name.span(),
+                "named slots can only be filled in direct children of a component",
+            ));
+        }
+        if let Some(Attribute::Case(case)) = node
+            .attributes()
+            .iter()
+            .find(|attr| matches!(attr, Attribute::Case(_)))
+        {
+            return Err(syn::Error::new(
+                case.pat.span(),
+                "`:case` can only be used on direct children of `<template :match>`",
+            ));
+        }
+    }
+    chain_conditionals(attach_empty_branches(nodes)?)
+}
+
+/// Moves every `:empty` node into the `:for` of the node right before it.
+fn attach_empty_branches(nodes: Vec<Node>) -> Result<Vec<Node>> {
+    let mut result: Vec<Node> = Vec::new();
+    for mut node in nodes {
+        let Some(Attribute::Empty(empty)) = node
+            .attributes()
+            .iter()
+            .find(|attr| matches!(attr, Attribute::Empty(_)))
+            .cloned()
+        else {
+            result.push(node);
+            continue;
+        };
+
+        let previous_for = result.last_mut().and_then(|previous| {
+            previous
+                .attributes_mut()?
+                .iter_mut()
+                .find_map(|attr| match attr {
+                    Attribute::For(for_expr) if for_expr.empty.is_none() => Some(for_expr),
+                    _ => None,
+                })
+        });
+        let Some(for_expr) = previous_for else {
+            return Err(syn::Error::new(
+                empty.span(),
+                "`:empty` must directly follow an element or component with `:for`",
+            ));
+        };
+        node.remove_attributes(|attr| matches!(attr, Attribute::Empty(_)));
+        for_expr.empty = Some(Box::new(node));
+    }
+    Ok(result)
+}
+
+fn match_attribute(attributes: &[Attribute]) -> Result<Option<Expr>> {
+    let Some(Attribute::Match(expr)) = attributes
+        .iter()
+        .find(|attr| matches!(attr, Attribute::Match(_)))
+    else {
+        return Ok(None);
+    };
+    if attributes.len() > 1 {
+        return Err(syn::Error::new(
+            expr.span(),
+            "`<template :match>` doesn't accept other attributes",
+        ));
+    }
+    Ok(Some(expr.clone()))
+}
+
+/// Splits the children of `<template :match>` into arms. An arm is a node with `:case` and the
+/// `:else-if`, `:else` and `:empty` siblings right after it, which are grouped like in any
+/// other list of children.
+fn match_arms(children: Vec<Node>) -> Result<Vec<(CaseExpr, Node)>> {
+    let mut arms: Vec<(CaseExpr, Vec<Node>)> = Vec::new();
+    for mut child in children {
+        let case = child.attributes().iter().find_map(|attr| match attr {
+            Attribute::Case(case) => Some(case.clone()),
+            _ => None,
+        });
+        let continues_arm = child.attributes().iter().any(|attr| {
+            matches!(
+                attr,
+                Attribute::ElseIf(_) | Attribute::Else(_) | Attribute::Empty(_)
+            )
+        });
+        match (case, arms.last_mut()) {
+            (Some(case), _) => {
+                child.remove_attributes(|attr| matches!(attr, Attribute::Case(_)));
+                arms.push((case, vec![child]));
+            }
+            (None, Some((_, arm))) if continues_arm => arm.push(child),
+            (None, _) => {
+                let span = match &child {
+                    Node::Element(tag) => tag.tag_name.span(),
+                    Node::Component(component) => component.name.span(),
+                    Node::Text(text) => text.span(),
+                    Node::Expression(expr) | Node::UnescapedExpression(expr) => expr.span(),
+                    _ => Span::call_site(),
+                };
+                return Err(syn::Error::new(
+                    span,
+                    "every child of `<template :match>` must be an element or component with `:case`",
+                ));
+            }
+        }
+    }
+
+    arms.into_iter()
+        .map(|(case, nodes)| {
+            let mut nodes = group_siblings(nodes)?;
+            let node = if nodes.len() == 1 {
+                nodes.remove(0)
+            } else {
+                Node::Fragment(Fragment {
+                    attributes: Vec::new(),
+                    children: nodes,
+                    context: None,
+                    fallback: None,
+                })
+            };
+            Ok((case, node))
+        })
+        .collect()
+}
+
+/// Merges every `:if` node and its directly following `:else-if` / `:else` siblings
+/// into a single [Node::Conditional].
+fn chain_conditionals(nodes: Vec<Node>) -> Result<Vec<Node>> {
+    let mut result = Vec::new();
+    let mut nodes = nodes.into_iter().peekable();
+
+    while let Some(mut node) = nodes.next() {
+        let condition = match conditional_attribute(&node)? {
+            None => {
+                result.push(node);
+                continue;
+            }
+            Some(Attribute::If(condition)) => condition.clone(),
+            Some(attr) => {
+                return Err(syn::Error::new(
+                    conditional_span(attr),
+                    "`:else-if` and `:else` must directly follow an element or component with `:if` or `:else-if`",
+                ));
+            }
+        };
+
+        let continues_chain = |node: &Node| {
+            node.attributes()
+                .iter()
+                .any(|attr| matches!(attr, Attribute::ElseIf(_) | Attribute::Else(_)))
+        };
+        if !nodes.peek().is_some_and(continues_chain) {
+            result.push(node);
+            continue;
+        }
+
+        node.remove_attributes(Attribute::is_conditional);
+        let mut conditional = Conditional {
+            branches: vec![(condition, node)],
+            else_branch: None,
+        };
+        while let Some(mut next) = nodes.next_if(continues_chain) {
+            let attr = conditional_attribute(&next)?.cloned();
+            next.remove_attributes(Attribute::is_conditional);
+            match attr {
+                Some(Attribute::ElseIf(condition)) => conditional.branches.push((condition, next)),
+                _ => {
+                    conditional.else_branch = Some(Box::new(next));
+                    break;
+                }
+            }
+        }
+        result.push(Node::Conditional(conditional));
+    }
+
+    Ok(result)
+}
+
+fn conditional_attribute(node: &Node) -> Result<Option<&Attribute>> {
+    let mut conditionals = node
+        .attributes()
+        .iter()
+        .filter(|attr| attr.is_conditional());
+    let first = conditionals.next();
+    if let Some(second) = conditionals.next() {
+        return Err(syn::Error::new(
+            conditional_span(second),
+            "only one of `:if`, `:else-if` and `:else` can be used on the same node",
+        ));
+    }
+    Ok(first)
+}
+
+fn conditional_span(attr: &Attribute) -> Span {
+    match attr {
+        Attribute::If(expr) | Attribute::ElseIf(expr) => expr.span(),
+        Attribute::Else(token) => token.span,
+        _ => Span::call_site(),
     }
 }
 
@@ -0,0 +1,71 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{Attribute, Expr, Result};
+
+/// Options of a component parameter set with `#[prop(...)]`.
+#[derive(Default)]
+pub struct PropOptions {
+    /// `Some(None)` for `default`, `Some(Some(expr))` for `default = expr`.
+    pub default: Option<Option<Expr>>,
+    pub optional: bool,
+    pub into: bool,
+}
+
+impl PropOptions {
+    pub fn from_attributes(attrs: &[Attribute]) -> Result<Self> {
+        let mut options = PropOptions::default();
+        for attr in attrs.iter().filter(|attr| is_prop_attribute(attr)) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("default") {
+                    options.default = if meta.input.peek(syn::Token![=]) {
+                        Some(Some(meta.value()?.parse()?))
+                    } else {
+                        Some(None)
+                    };
+                } else if meta.path.is_ident("optional") {
+                    options.optional = true;
+                } else if meta.path.is_ident("into") {
+                    options.into = true;
+                } else {
+                    return Err(
+                        meta.error("expected `default`, `default = ...`, `optional` or `into`")
+                    );
+                }
+                Ok(())
+            })?;
+        }
+        Ok(options)
+    }
+
+    /// The `#[builder(...)]` attribute for the field of the props struct.
+    pub fn builder_attribute(&self) -> TokenStream2 {
+        let mut args = Vec::new();
+        match &self.default {
+            Some(Some(expr)) => args.push(quote! { default = #expr }),
+            Some(None) => args.push(quote! { default }),
+            None if self.optional => args.push(quote! { default }),
+            None => {}
+        }
+
+        let mut setter = Vec::new();
+        if self.optional {
+            setter.push(quote! { strip_option });
+        }
+        if self.into {
+            setter.push(quote! { into });
+        }
+        if !setter.is_empty() {
+            args.push(quote! { setter(#(#setter),*) });
+        }
+
+        if args.is_empty() {
+            quote! {}
+        } else {
+            quote! { #[builder(#(#args),*)] }
+        }
+    }
+}
+
+pub fn is_prop_attribute(attr: &Attribute) -> bool {
+    attr.path().is_ident("prop")
+}